@@ -0,0 +1,60 @@
+//! Time the x64 JIT running `.` and `,` through the buffers of `VMStruct`
+//! against a call into Rust for every byte, as with `--unbuffered`.
+//!
+//! Run with `cargo bench --bench io`.
+
+#![allow(clippy::needless_return)]
+
+use std::io::Cursor;
+use std::time::{Duration, Instant};
+
+use bfjit::bftype::bfcate::bfcate::{VMBackendType, VMEofType};
+use bfjit::bfvm::bfjit::vm;
+use bfjit::{Options, Program};
+
+// prints 'A' 255^3 times
+const WRITE: &str = "++++++++[>++++++++<-]>+>-[>-[>-[<<<.>>>-]<-]<-]";
+// copies its input until the end
+const COPY: &str = ",[.,]";
+const INPUT_SIZE: usize = 1 << 24;
+const RUNS: usize = 5;
+
+fn best(src: &str, input: &[u8], output_buffer: usize) -> Duration {
+    let options = Options {
+        backend: VMBackendType::JIT,
+        eof_type: VMEofType::Zero,
+        output_buffer,
+        input_buffer: if output_buffer > 0 {
+            vm::INPUT_BUFFER_SIZE
+        } else {
+            0
+        },
+        ..Default::default()
+    };
+    let program = Program::compile(src, options).expect("compile");
+    return (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            program
+                .run(Cursor::new(input), std::io::sink())
+                .expect("run");
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+}
+
+fn main() {
+    let input = vec![b'a'; INPUT_SIZE];
+    for (name, src, input) in [("write", WRITE, &[][..]), ("copy", COPY, &input[..])] {
+        let batched = best(src, input, vm::OUTPUT_BUFFER_SIZE);
+        let per_byte = best(src, input, 0);
+        println!(
+            "{:<6} batched {:>8.2?}  per byte {:>8.2?}  speedup {:.1}x",
+            name,
+            batched,
+            per_byte,
+            per_byte.as_secs_f64() / batched.as_secs_f64()
+        );
+    }
+}
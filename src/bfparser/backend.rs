@@ -1,91 +1,558 @@
 pub mod codegen {
     use dynasm::dynasm;
+    use dynasmrt::aarch64::Aarch64Relocation;
     use dynasmrt::relocations::Relocation;
     use dynasmrt::x64::X64Relocation;
+    use dynasmrt::x86::X86Relocation;
     use dynasmrt::DynasmLabelApi;
-    use dynasmrt::{Assembler, DynasmApi};
+    use dynasmrt::{Assembler, AssemblyOffset, DynasmApi};
 
-    use crate::bfparser::frontend::ir::BFIR;
-    use crate::bftype::bfcate::bfcate::VMArchType;
+    use crate::bfparser::backend::elf;
+    use crate::bfparser::frontend::ir::{IRNode, Sources, BFIR};
+    use crate::bftype::bfcate::bfcate::{VMArchType, VMCellType, VMEofType};
     use crate::bftype::bferror;
     use crate::bfvm::bfjit::vm;
+    use crate::Options;
+
+    /// Where the code of each instruction starts, instructions being
+    /// numbered by their preorder position like in runtime errors.
+    #[derive(Debug, Default)]
+    pub struct CodeMap {
+        position: usize,
+        /// `(code offset, position)` pairs in code order. Loops appear twice,
+        /// at their first test and at the test closing them.
+        pub marks: Vec<(usize, usize)>,
+        /// Sources of each instruction, by position.
+        pub sources: Vec<Sources>,
+        /// `(return offset, position)` of the calls made when a block check
+        /// fails, in code order. The position is the one of the instruction
+        /// of the block reaching the furthest cell on that side.
+        pub checks: Vec<(usize, usize)>,
+    }
+
+    impl CodeMap {
+        fn mark(&mut self, offset: AssemblyOffset, sources: Sources) -> usize {
+            let position = self.position;
+            self.position += 1;
+            self.marks.push((offset.0, position));
+            self.sources.push(sources);
+            return position;
+        }
+
+        fn check(&mut self, offset: AssemblyOffset, position: usize) {
+            self.checks.push((offset.0, position));
+        }
+
+        /// The position and sources of the instruction making the call which
+        /// returns to `offset`.
+        pub fn locate(&self, offset: usize) -> Option<(usize, Sources)> {
+            let position = match self.checks.binary_search_by_key(&offset, |&(ret, _)| ret) {
+                Ok(index) => self.checks[index].1,
+                Err(_) => {
+                    // the return address may be the start of the next instruction
+                    let index = self.marks.partition_point(|&(start, _)| start < offset);
+                    self.marks.get(index.checked_sub(1)?)?.1
+                }
+            };
+            return Some((position, self.sources[position]));
+        }
+    }
+
+    /// Byte displacement of the cell `offset` cells away from the pointer,
+    /// `None` if it doesn't fit in an addressing mode.
+    fn cell_disp(cell_type: VMCellType, offset: i64) -> Option<i32> {
+        return offset
+            .checked_mul(cell_type.bytes() as i64)
+            .and_then(|x| i32::try_from(x).ok())
+            .filter(|x| *x != i32::MIN);
+    }
+
+    /// A run of instructions bounds checked at once before it starts,
+    /// `min` and `max` being the lowest and highest cells it touches, first
+    /// by the instructions `min_at` and `max_at` from its start.
+    struct Block {
+        end: usize,
+        min: i64,
+        max: i64,
+        min_at: usize,
+        max_at: usize,
+    }
+
+    /// Find the block starting at `irs[start]`. It stops after a move, and
+    /// after anything that may raise an error so that errors keep their order.
+    fn scan_block(irs: &Vec<IRNode>, start: usize, options: &Options) -> Block {
+        let mut block = Block {
+            end: start,
+            min: 0,
+            max: 0,
+            min_at: 0,
+            max_at: 0,
+        };
+        for ir in &irs[start..] {
+            let (offset, last) = match &ir.ir {
+                BFIR::Add(_, o) | BFIR::Sub(_, o) => (*o, options.checked_cells),
+                BFIR::SetZero(o) => (*o, false),
+                // the target is only touched when `ptr[base]` isn't zero
+                BFIR::MulAdd { base, .. } => (*base, false),
+                BFIR::Input(o) | BFIR::Output(o) => (*o, true),
+                BFIR::MoveLeft(x) => (-(*x as i64), true),
+                BFIR::MoveRight(x) => (*x as i64, true),
+                BFIR::Scan { .. } | BFIR::Loop(_) => break,
+            };
+            if offset < block.min {
+                block.min = offset;
+                block.min_at = block.end - start;
+            }
+            if offset > block.max {
+                block.max = offset;
+                block.max_at = block.end - start;
+            }
+            block.end += 1;
+            if last {
+                break;
+            }
+        }
+        return block;
+    }
+
+    fn gen_x64_add(ops: &mut Assembler<X64Relocation>, cell_type: VMCellType, x: u32, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; add BYTE [rcx + disp], x as i8     // ptr[disp] += x
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; add WORD [rcx + disp], x as i16
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; add DWORD [rcx + disp], x as i32
+            ),
+            VMCellType::U64 if x <= i32::MAX as u32 => dynasm!(ops
+                ; add QWORD [rcx + disp], x as i32
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; mov rax, QWORD x as i64
+                ; add QWORD [rcx + disp], rax
+            ),
+        }
+    }
+
+    fn gen_x64_sub(ops: &mut Assembler<X64Relocation>, cell_type: VMCellType, x: u32, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; sub BYTE [rcx + disp], x as i8     // ptr[disp] -= x
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; sub WORD [rcx + disp], x as i16
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; sub DWORD [rcx + disp], x as i32
+            ),
+            VMCellType::U64 if x <= i32::MAX as u32 => dynasm!(ops
+                ; sub QWORD [rcx + disp], x as i32
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; mov rax, QWORD x as i64
+                ; sub QWORD [rcx + disp], rax
+            ),
+        }
+    }
+
+    fn gen_x64_set_zero(ops: &mut Assembler<X64Relocation>, cell_type: VMCellType, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; mov BYTE [rcx + disp], 0     // ptr[disp] = 0
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; mov WORD [rcx + disp], 0
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; mov DWORD [rcx + disp], 0
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; mov QWORD [rcx + disp], 0
+            ),
+        }
+    }
+
+    /// Emit `ptr[base + offset] += ptr[base] * factor`, skipped when
+    /// `ptr[base]` is zero so that an out of range `offset` only fails when
+    /// the loop would have run. `step` is `base + offset` in bytes.
+    fn gen_x64_mul_add(
+        ops: &mut Assembler<X64Relocation>,
+        options: &Options,
+        disp: i32,
+        step: i64,
+        factor: i64,
+    ) {
+        let cell_type = options.cell_type;
+        dynasm!(ops
+            ; again:
+        );
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; movzx eax, BYTE [rcx + disp]
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; movzx eax, WORD [rcx + disp]
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; mov eax, DWORD [rcx + disp]
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; mov rax, QWORD [rcx + disp]
+            ),
+        }
+        dynasm!(ops
+            ; test rax, rax
+            ; jz   >skip            // skip if ptr[base] == 0
+        );
+        match i32::try_from(factor) {
+            Ok(x) => dynasm!(ops
+                ; imul rax, rax, x      // rax = ptr[base] * factor
+            ),
+            Err(_) => dynasm!(ops
+                ; mov  rdx, QWORD factor
+                ; imul rax, rdx
+            ),
+        }
+        match i32::try_from(step) {
+            Ok(x) => dynasm!(ops
+                ; lea  rdx, [rcx + x]   // rdx = ptr + base + offset
+            ),
+            Err(_) => dynasm!(ops
+                ; mov  rdx, QWORD step
+                ; add  rdx, rcx
+            ),
+        }
+        if options.growable {
+            dynasm!(ops
+                ; cmp  rdx, r13
+                ; jb   >grow
+                ; cmp  rdx, r14
+                ; jb   >done
+                ; grow:
+                ; mov  rcx, rdx         // grow around ptr + offset
+                ; call ->grow
+                ; mov  rdx, QWORD step
+                ; sub  rcx, rdx
+                ; jmp  <again
+                ; done:
+            );
+        } else {
+            dynasm!(ops
+                ; cmp  rdx, r13
+                ; jb   >outside         // jmp if ptr + offset < memory_start
+                ; cmp  rdx, r14
+                ; jb   >inside          // ok if ptr + offset < memory_end
+                ; outside:
+            );
+            // the pointer is inside, so only one end can be crossed
+            if step < 0 {
+                dynasm!(ops
+                    ; call ->underflow
+                );
+            } else {
+                dynasm!(ops
+                    ; call ->overflow
+                );
+            }
+            dynasm!(ops
+                ; inside:
+            );
+        }
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; add [rdx], al
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; add [rdx], ax
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; add [rdx], eax
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; add [rdx], rax
+            ),
+        }
+        dynasm!(ops
+            ; skip:
+        );
+    }
+
+    fn gen_x64_cmp_zero(ops: &mut Assembler<X64Relocation>, cell_type: VMCellType) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; cmp BYTE [rcx], 0
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; cmp WORD [rcx], 0
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; cmp DWORD [rcx], 0
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; cmp QWORD [rcx], 0
+            ),
+        }
+    }
+
+    /// Emit `ptr += step` or `ptr -= step`, `step` being in bytes.
+    fn gen_x64_step(ops: &mut Assembler<X64Relocation>, step: i64, right: bool) {
+        match (i32::try_from(step), right) {
+            (Ok(x), true) => dynasm!(ops
+                ; add rcx, x
+            ),
+            (Ok(x), false) => dynasm!(ops
+                ; sub rcx, x
+            ),
+            (Err(_), true) => dynasm!(ops
+                ; mov rax, QWORD step
+                ; add rcx, rax
+            ),
+            (Err(_), false) => dynasm!(ops
+                ; mov rax, QWORD step
+                ; sub rcx, rax
+            ),
+        }
+    }
+
+    /// Raise `CellOverflow` for instruction `ir_index` if the last add/sub
+    /// carried, or unconditionally if `x` doesn't fit in a cell.
+    fn gen_x64_check(
+        ops: &mut Assembler<X64Relocation>,
+        cell_type: VMCellType,
+        x: u32,
+        ir_index: usize,
+    ) {
+        let fits = cell_type.bytes() >= 4 || x < 1 << (8 * cell_type.bytes());
+        if fits {
+            dynasm!(ops
+                ; jnc  >ok              // jmp if no carry
+            );
+        }
+        dynasm!(ops
+            ; mov  rdi, QWORD ir_index as i64
+            ; call ->cell_overflow  // cell_overflow(index)
+            ; jmp  ->io_error
+        );
+        if fits {
+            dynasm!(ops
+                ; ok:
+            );
+        }
+    }
+
+    /// Check that the cells from `ptr[block.min]` to `ptr[block.max]` are
+    /// inside the memory, growing it in growable mode. `position` is the one
+    /// of the first instruction of the block.
+    fn gen_x64_bounds(
+        ops: &mut Assembler<X64Relocation>,
+        options: &Options,
+        block: &Block,
+        position: usize,
+        map: &mut CodeMap,
+    ) {
+        let (lo, hi) = match (
+            cell_disp(options.cell_type, block.min),
+            cell_disp(options.cell_type, block.max),
+        ) {
+            (Some(lo), Some(hi)) => (lo, hi),
+            (lo, _) => {
+                let at = if lo.is_none() {
+                    dynasm!(ops
+                        ; call ->underflow
+                    );
+                    block.min_at
+                } else {
+                    dynasm!(ops
+                        ; call ->overflow
+                    );
+                    block.max_at
+                };
+                map.check(ops.offset(), position + at);
+                return;
+            }
+        };
+        dynasm!(ops
+            ; again:
+        );
+        if lo < 0 {
+            dynasm!(ops
+                ; mov  rax, rcx
+                ; sub  rax, r13
+                ; cmp  rax, -lo
+                ; jnb  >low             // ok if ptr + min >= memory_start
+            );
+            if options.growable {
+                dynasm!(ops
+                    ; add  rcx, lo          // grow around ptr + min
+                    ; call ->grow
+                );
+                map.check(ops.offset(), position + block.min_at);
+                dynasm!(ops
+                    ; sub  rcx, lo
+                    ; jmp  <again
+                );
+            } else {
+                dynasm!(ops
+                    ; call ->underflow
+                );
+                map.check(ops.offset(), position + block.min_at);
+            }
+            dynasm!(ops
+                ; low:
+            );
+        }
+        if hi > 0 {
+            dynasm!(ops
+                ; mov  rax, r14
+                ; sub  rax, rcx
+                ; cmp  rax, hi
+                ; ja   >high            // ok if ptr + max < memory_end
+            );
+            if options.growable {
+                dynasm!(ops
+                    ; add  rcx, hi          // grow around ptr + max
+                    ; call ->grow
+                );
+                map.check(ops.offset(), position + block.max_at);
+                dynasm!(ops
+                    ; sub  rcx, hi
+                    ; jmp  <again
+                );
+            } else {
+                dynasm!(ops
+                    ; call ->overflow
+                );
+                map.check(ops.offset(), position + block.max_at);
+            }
+            dynasm!(ops
+                ; high:
+            );
+        }
+    }
 
     fn gen_x64_code_normal(
-        irs: &Vec<BFIR>,
+        irs: &Vec<IRNode>,
         mut ops: Box<Assembler<X64Relocation>>,
+        options: &Options,
+        map: &mut CodeMap,
     ) -> Box<Assembler<X64Relocation>> {
+        let cell_type = options.cell_type;
+        let cell_bytes = cell_type.bytes() as i64;
+        // out of range displacements already fail in the block check
+        let disp = |offset: i64| cell_disp(cell_type, offset).unwrap_or(0);
         let mut index = 0;
+        let mut block_end = 0;
         let len = irs.len();
         while index < len {
-            match &irs[index] {
-                BFIR::Add(x) => {
+            // preorder position of the instruction, reported by runtime errors
+            let ir_index = map.mark(ops.offset(), irs[index].sources());
+            if index >= block_end && !matches!(irs[index].ir, BFIR::Loop(_) | BFIR::Scan { .. }) {
+                let block = scan_block(irs, index, options);
+                gen_x64_bounds(&mut ops, options, &block, ir_index, map);
+                block_end = block.end;
+            }
+            match &irs[index].ir {
+                BFIR::Add(x, o) => {
                     index += 1;
-                    dynasm!(ops
-                        ; add BYTE [rcx], *x as i8    // *ptr += x
-                    );
+                    gen_x64_add(&mut ops, cell_type, *x, disp(*o));
+                    if options.checked_cells {
+                        gen_x64_check(&mut ops, cell_type, *x, ir_index);
+                    }
                 }
-                BFIR::Sub(x) => {
+                BFIR::Sub(x, o) => {
                     index += 1;
-                    dynasm!(ops
-                        ; sub BYTE [rcx], *x as i8    // *ptr -= x
-                    );
+                    gen_x64_sub(&mut ops, cell_type, *x, disp(*o));
+                    if options.checked_cells {
+                        gen_x64_check(&mut ops, cell_type, *x, ir_index);
+                    }
+                }
+                BFIR::SetZero(o) => {
+                    index += 1;
+                    gen_x64_set_zero(&mut ops, cell_type, disp(*o));
+                }
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => {
+                    index += 1;
+                    let step = (base + offset).wrapping_mul(cell_bytes);
+                    gen_x64_mul_add(&mut ops, options, disp(*base), step, *factor);
                 }
+                // the target was checked with the block
                 BFIR::MoveLeft(x) => {
                     index += 1;
-                    dynasm!(ops
-                        ; sub rcx, *x as i32     // ptr -= x
-                        ; jc  ->overflow        // jmp if overflow
-                        ; cmp rcx, r13          // ptr - memory_start
-                        ; jb  ->overflow        // jmp if ptr < memory_start
-                    );
+                    gen_x64_step(&mut ops, *x as i64 * cell_bytes, false);
                 }
                 BFIR::MoveRight(x) => {
                     index += 1;
-                    dynasm!(ops
-                        ; add rcx, *x as i32     // ptr += x
-                        ; jc  ->overflow        // jmp if overflow
-                        ; cmp rcx, r14          // ptr - memory_end
-                        ; jnb ->overflow        // jmp if ptr >= memory_end
-                    );
+                    gen_x64_step(&mut ops, *x as i64 * cell_bytes, true);
                 }
-                BFIR::Input => {
+                BFIR::Input(o) => {
                     index += 1;
+                    let o = disp(*o);
+                    if vm::batched_io(options) {
+                        gen_x64_read_buffered(&mut ops, options, o);
+                    }
                     dynasm!(ops
                         ; mov  r15, rcx         // save ptr
                         ; mov  rdi, r12
-                        ; mov  rsi, rcx         // arg0: this, arg1: ptr
-                        ; mov  rax, QWORD vm::VMStruct::put_x64_byte as _
-                        ; call rax              // getbyte(this, ptr)
+                        ; lea  rsi, [rcx + o]   // arg0: this, arg1: ptr + offset
+                        ; call ->put_byte       // getbyte(this, ptr + offset)
                         ; test rax, rax
                         ; jnz  ->io_error       // jmp if rax != 0
                         ; mov  rcx, r15         // recover ptr
-                    )
+                    );
+                    if vm::batched_io(options) {
+                        dynasm!(ops
+                            ; done:
+                        );
+                    }
                 }
-                BFIR::Output => {
+                BFIR::Output(o) => {
                     index += 1;
+                    let o = disp(*o);
+                    if vm::batched_io(options) {
+                        gen_x64_write_buffered(&mut ops, options, o);
+                        continue;
+                    }
                     dynasm!(ops
                         ; mov  r15, rcx         // save ptr
                         ; mov  rdi, r12
-                        ; mov  rsi, rcx         // arg0: this, arg1: ptr
-                        ; mov  rax, QWORD vm::VMStruct::get_x64_byte as _
-                        ; call rax              // putbyte(this, ptr)
+                        ; lea  rsi, [rcx + o]   // arg0: this, arg1: ptr + offset
+                        ; call ->get_byte       // putbyte(this, ptr + offset)
                         ; test rax, rax
                         ; jnz  ->io_error       // jmp if rax != 0
                         ; mov  rcx, r15         // recover ptr
                     )
                 }
+                BFIR::Scan { stride } => {
+                    index += 1;
+                    gen_x64_cmp_zero(&mut ops, cell_type);
+                    dynasm!(ops
+                        ; jz   >done            // nothing to do if *ptr == 0
+                        ; mov  rdx, QWORD stride.wrapping_mul(cell_bytes)
+                        ; call ->scan           // scan(ptr, step)
+                        ; test rax, rax
+                        ; jnz  ->io_error       // jmp if rax != 0
+                        ; done:
+                    );
+                }
                 BFIR::Loop(x) => {
                     index += 1;
                     let left = ops.new_dynamic_label();
                     let right = ops.new_dynamic_label();
+                    gen_x64_cmp_zero(&mut ops, cell_type);
                     dynasm!(ops
-                        ; cmp BYTE [rcx], 0
                         ; jz => right       // jmp if *ptr == 0
                         ; => left
                     );
-                    ops = gen_x64_code_normal(&x.borrow(), ops);
+                    ops = gen_x64_code_normal(&x.borrow(), ops, options, map);
+                    map.marks.push((ops.offset().0, ir_index));
+                    gen_x64_cmp_zero(&mut ops, cell_type);
                     dynasm!(ops
-                        ; cmp BYTE [rcx], 0
                         ; jnz => left       // jmp if *ptr != 0
                         ; => right
                     );
@@ -95,54 +562,3012 @@ pub mod codegen {
         return ops;
     }
 
-    fn gen_x64_code(
-        irs: &Vec<BFIR>,
-    ) -> Result<Assembler<impl Relocation + std::fmt::Debug>, bferror::error::RuntimeError> {
-        let ops = dynasmrt::x64::Assembler::new();
-        if ops.is_err() {
-            return Err(bferror::error::RuntimeError {
-                index: 1,
-                kind: bferror::error::RuntimeErrorKind::Memory,
-            });
+    /// Read `ptr[o]` from the read-ahead buffer of `VMStruct` and jump to the
+    /// `done` label following the `->put_byte` call refilling it, which is
+    /// reached when the buffer is empty.
+    fn gen_x64_read_buffered(ops: &mut Assembler<X64Relocation>, options: &Options, o: i32) {
+        let (in_ptr, in_end, _, _) = vm::io_offsets();
+        dynasm!(ops
+            ; mov  rdx, [r12 + in_ptr]
+            ; cmp  rdx, [r12 + in_end]
+            ; jae  >empty               // call back if in_ptr >= in_end
+            ; movzx eax, BYTE [rdx]
+            ; inc  rdx
+            ; mov  [r12 + in_ptr], rdx
+        );
+        if options.add_48 {
+            dynasm!(ops
+                ; sub  al, 48
+                ; movzx eax, al
+            );
         }
-        let mut ops_ptr = Box::new(ops.unwrap());
-        dynasm!(ops_ptr
-            ; push rax
+        dynasm!(ops
+            ; lea  rdx, [rcx + o]
+        );
+        gen_x64_store(ops, options.cell_type);
+        dynasm!(ops
+            ; jmp  >done
+            ; empty:
+        );
+    }
+
+    /// Append the low byte of `ptr[o]` to the output buffer of `VMStruct`,
+    /// calling `->flush_output` before it is full and after a newline.
+    fn gen_x64_write_buffered(ops: &mut Assembler<X64Relocation>, options: &Options, o: i32) {
+        let (_, _, out_ptr, out_end) = vm::io_offsets();
+        dynasm!(ops
+            ; mov  rdx, [r12 + out_ptr]
+            ; cmp  rdx, [r12 + out_end]
+            ; jb   >room                // jmp if out_ptr < out_end
+            ; call ->flush_output
+            ; test rax, rax
+            ; jnz  ->io_error           // jmp if rax != 0
+            ; mov  rdx, [r12 + out_ptr]
+            ; room:
+            ; mov  al, [rcx + o]
+        );
+        if options.add_48 {
+            dynasm!(ops
+                ; add  al, 48
+            );
+        }
+        dynasm!(ops
+            ; mov  [rdx], al
+            ; inc  rdx
+            ; mov  [r12 + out_ptr], rdx
+            ; cmp  al, 10
+            ; jne  >done                // flush at the end of a line
+            ; call ->flush_output
+            ; test rax, rax
+            ; jnz  ->io_error           // jmp if rax != 0
+            ; done:
+        );
+    }
+
+    fn gen_x64_function(
+        irs: &Vec<IRNode>,
+        mut ops: Box<Assembler<X64Relocation>>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Box<Assembler<X64Relocation>> {
+        // sysv64: fn(this, memory_start, memory_end) -> error
+        dynasm!(ops
+            ; -> main:
+            ; push r12       // save callee-saved registers
+            ; push r13
+            ; push r14
+            ; push r15
+            ; push rax       // align stack to 16 bytes
             ; mov r12, rdi   // save this
             ; mov r13, rsi   // save memory_start
             ; mov r14, rdx   // save memory_end
             ; mov rcx, rsi   // ptr = memory_start
         );
-        ops_ptr = gen_x64_code_normal(&irs, ops_ptr);
-        dynasm!(ops_ptr
+        ops = gen_x64_code_normal(irs, ops, options, map);
+        dynasm!(ops
             ; xor rax, rax
             ; jmp >exit
+            ; -> underflow:             // called by the failing check
+            ; mov edi, 1                // below = true
+            ; jmp >fail
             ; -> overflow:
-            ; mov rax, QWORD vm::VMStruct::overflow_error as _
-            ; call rax
+            ; xor edi, edi
+            ; fail:
+            ; call ->overflow_error
+            ; add rsp, 8                // drop return address
             ; jmp >exit
             ; -> io_error:
             ; exit:
             ; pop rdx
+            ; pop r15
+            ; pop r14
+            ; pop r13
+            ; pop r12
             ; ret
         );
-        return Ok(*ops_ptr);
+        return ops;
     }
 
-    pub fn gen_code(
-        irs: &Vec<BFIR>,
-        vm_arch_type: VMArchType,
-    ) -> Result<Assembler<impl Relocation + std::fmt::Debug>, bferror::error::RuntimeError> {
-        match vm_arch_type {
-            VMArchType::X64 => {
-                return gen_x64_code(irs);
+    /// Subroutines calling back into `VMStruct`, only usable on an x86_64 host.
+    /// `->scan` and `->grow` reload `memory_start`, `memory_end` and `ptr`.
+    /// They all first save their return address, which is in the code of the
+    /// calling instruction, to `VMStruct::fault`.
+    #[cfg(target_arch = "x86_64")]
+    fn gen_x64_callbacks(
+        mut ops: Box<Assembler<X64Relocation>>,
+        options: &Options,
+    ) -> Result<Box<Assembler<X64Relocation>>, bferror::error::RuntimeError> {
+        let (start, end, ptr) = vm::tape_offsets();
+        let fault = vm::fault_offset();
+        dynasm!(ops
+            ; -> put_byte:
+            ; mov  rax, [rsp]
+            ; mov  [r12 + fault], rax
+            ; mov  rax, QWORD vm::VMStruct::put_x64_byte as *const () as i64
+            ; jmp  rax
+            ; -> get_byte:
+            ; mov  rax, [rsp]
+            ; mov  [r12 + fault], rax
+            ; mov  rax, QWORD vm::VMStruct::get_x64_byte as *const () as i64
+            ; jmp  rax
+            ; -> overflow_error:
+            ; mov  rax, [rsp + 8]       // return address of the call to ->overflow
+            ; mov  [r12 + fault], rax
+            ; mov  rax, QWORD vm::VMStruct::overflow_error as *const () as i64
+            ; call rax                  // the stack is aligned after two calls
+            ; ret
+            ; -> cell_overflow:
+            ; mov  rax, [rsp]
+            ; mov  [r12 + fault], rax
+            ; mov  rax, QWORD vm::VMStruct::cell_overflow_x64 as *const () as i64
+            ; jmp  rax
+            ; -> flush_output:
+            ; mov  rax, [rsp]
+            ; mov  [r12 + fault], rax
+            ; sub  rsp, 8               // align stack to 16 bytes
+            ; mov  r15, rcx             // save ptr
+            ; mov  rdi, r12             // arg0: this
+            ; mov  rax, QWORD vm::VMStruct::flush_x64_output as *const () as i64
+            ; call rax                  // flush_output(this)
+            ; mov  rcx, r15             // recover ptr
+            ; add  rsp, 8
+            ; ret
+            ; -> scan:
+            ; mov  rax, [rsp]
+            ; mov  [r12 + fault], rax
+            ; sub  rsp, 8               // align stack to 16 bytes
+            ; mov  rdi, r12
+            ; mov  rsi, rcx
+            ; sub  rsi, r13             // arg0: this, arg1: ptr - memory_start, arg2: step
+            ; mov  rax, QWORD vm::VMStruct::scan_x64 as *const () as i64
+            ; call rax                  // scan(this, offset, step)
+            ; add  rsp, 8
+            ; mov  r13, [r12 + start]
+            ; mov  r14, [r12 + end]
+            ; mov  rcx, [r12 + ptr]
+            ; ret
+        );
+        if options.growable {
+            // grow the memory, then reload memory_start, memory_end and ptr
+            dynasm!(ops
+                ; -> grow:
+                ; mov  rax, [rsp]
+                ; mov  [r12 + fault], rax
+                ; sub  rsp, 8               // align stack to 16 bytes
+                ; mov  rdi, r12
+                ; mov  rsi, rcx
+                ; sub  rsi, r13             // arg0: this, arg1: ptr - memory_start
+                ; mov  rax, QWORD vm::VMStruct::grow_x64 as *const () as i64
+                ; call rax                  // grow(this, offset)
+                ; add  rsp, 8
+                ; test rax, rax
+                ; jnz  >fail                // jmp if rax != 0
+                ; mov  r13, [r12 + start]
+                ; mov  r14, [r12 + end]
+                ; mov  rcx, [r12 + ptr]
+                ; ret
+                ; fail:
+                ; add  rsp, 8               // drop return address
+                ; jmp  ->io_error
+            );
+        }
+        return Ok(ops);
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    fn gen_x64_callbacks(
+        _ops: Box<Assembler<X64Relocation>>,
+        _options: &Options,
+    ) -> Result<Box<Assembler<X64Relocation>>, bferror::error::RuntimeError> {
+        return Err(vm::wrong_host(&VMArchType::X64));
+    }
+
+    /// Store `rax` into the cell at `rdx`.
+    fn gen_x64_store(ops: &mut Assembler<X64Relocation>, cell_type: VMCellType) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; mov [rdx], al
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; mov [rdx], ax
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; mov [rdx], eax
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; mov [rdx], rax
+            ),
+        }
+    }
+
+    /// Linux `syscall` versions of the callbacks for standalone executables,
+    /// returning 0 on success or the exit status otherwise.
+    fn gen_x64_syscalls(
+        mut ops: Box<Assembler<X64Relocation>>,
+        options: &Options,
+    ) -> Box<Assembler<X64Relocation>> {
+        let cell_type = options.cell_type;
+        dynasm!(ops
+            ; -> put_byte:
+            ; push rsi                  // ptr
+            ; push 0                    // byte buffer
+            ; xor  eax, eax             // read(0, buf, 1)
+            ; xor  edi, edi
+            ; mov  rsi, rsp
+            ; mov  edx, 1
+            ; syscall
+            ; mov  rdx, [rsp + 8]
+            ; cmp  rax, 1
+            ; jne  >eof
+            ; movzx eax, BYTE [rsp]
+        );
+        gen_x64_store(&mut ops, cell_type);
+        dynasm!(ops
+            ; jmp  >done
+            ; eof:
+            ; test rax, rax
+            ; jnz  >fail                // jmp if read failed
+        );
+        match options.eof_type {
+            VMEofType::Error => dynasm!(ops
+                ; jmp  >fail
+            ),
+            VMEofType::Zero => {
+                dynasm!(ops
+                    ; xor  eax, eax
+                );
+                gen_x64_store(&mut ops, cell_type);
+            }
+            VMEofType::MinusOne => {
+                dynasm!(ops
+                    ; mov  rax, -1
+                );
+                gen_x64_store(&mut ops, cell_type);
+            }
+            VMEofType::Unchanged => (),
+        }
+        dynasm!(ops
+            ; done:
+            ; xor  eax, eax
+            ; jmp  >exit
+            ; fail:
+            ; mov  eax, 1
+            ; exit:
+            ; add  rsp, 16
+            ; ret
+            ; -> get_byte:
+            ; mov  eax, 1               // write(1, ptr, 1)
+            ; mov  edi, 1
+            ; mov  edx, 1
+            ; syscall
+            ; cmp  rax, 1
+            ; setne al
+            ; movzx eax, al
+            ; ret
+            ; -> overflow_error:
+            ; mov  eax, 2
+            ; ret
+            ; -> cell_overflow:
+            ; mov  eax, 3
+            ; ret
+            ; -> scan:
+            ; again:
+        );
+        gen_x64_cmp_zero(&mut ops, cell_type);
+        dynasm!(ops
+            ; jz   >found               // stop if *ptr == 0
+            ; lea  rax, [rcx + rdx]
+            ; cmp  rax, r13
+            ; jb   >fail                // jmp if ptr + step < memory_start
+            ; cmp  rax, r14
+            ; jnb  >fail                // jmp if ptr + step >= memory_end
+            ; mov  rcx, rax
+            ; jmp  <again
+            ; found:
+            ; xor  eax, eax
+            ; ret
+            ; fail:
+            ; mov  eax, 2
+            ; ret
+        );
+        return ops;
+    }
+
+    fn new_x64_assembler() -> Result<Box<Assembler<X64Relocation>>, bferror::error::RuntimeError> {
+        let ops = dynasmrt::x64::Assembler::new();
+        if ops.is_err() {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        return Ok(Box::new(ops.unwrap()));
+    }
+
+    fn gen_x64_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<dynasmrt::ExecutableBuffer, bferror::error::RuntimeError> {
+        let mut ops_ptr = new_x64_assembler()?;
+        ops_ptr = gen_x64_function(irs, ops_ptr, options, map);
+        ops_ptr = gen_x64_callbacks(ops_ptr, options)?;
+        return finalize(*ops_ptr);
+    }
+
+    /// Code of the standalone 64-bit executable, loaded at `ELF64_ENTRY_ADDR`
+    /// and entered at its first byte, with the size of its memory.
+    pub fn gen_x64_elf_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<(dynasmrt::ExecutableBuffer, usize), bferror::error::RuntimeError> {
+        if options.growable {
+            // there is no runtime to grow the memory
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Unsupported(
+                    "growable tape is not supported by standalone executables",
+                ),
+                span: None,
+            });
+        }
+        let memory_size = vm::tape_bytes(options)?;
+        if memory_size == 0 || memory_size > elf::ELF64_MEMORY_MAX {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        // the executables make a syscall per byte, there is no `VMStruct`
+        // holding buffers
+        let options = &Options {
+            output_buffer: 0,
+            ..options.clone()
+        };
+        let memory_start = elf::ELF64_MEMORY_ADDR as i64;
+        let memory_end = memory_start + memory_size as i64;
+        let mut ops_ptr = new_x64_assembler()?;
+        dynasm!(ops_ptr
+            ; xor  edi, edi             // no VMStruct
+            ; mov  rsi, QWORD memory_start
+            ; mov  rdx, QWORD memory_end
+            ; call ->main
+            ; mov  edi, eax             // exit(error)
+            ; mov  eax, 60
+            ; syscall
+        );
+        ops_ptr = gen_x64_function(irs, ops_ptr, options, map);
+        ops_ptr = gen_x64_syscalls(ops_ptr, options);
+        return Ok((finalize(*ops_ptr)?, memory_size));
+    }
+
+    /// Generate a standalone 64-bit Linux ELF executable running `irs` on a
+    /// tape of `options.tape_size` cells, without any runtime.
+    ///
+    /// The exit status is 0 on success, 1 on an IO error, 2 when the pointer
+    /// leaves the memory and 3 on a cell overflow with `checked_cells`.
+    pub fn gen_x64_elf(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<Vec<u8>, bferror::error::RuntimeError> {
+        let (code, memory_size) = gen_x64_elf_code(irs, options, &mut CodeMap::default())?;
+        return Ok(elf::write_elf64(&code, memory_size, elf::EM_X86_64));
+    }
+
+    fn gen_x32_add(ops: &mut Assembler<X86Relocation>, cell_type: VMCellType, x: u32, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch x86
+                ; add BYTE [ecx + disp], x as i8     // ptr[disp] += x
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch x86
+                ; add WORD [ecx + disp], x as i16
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch x86
+                ; add DWORD [ecx + disp], x as i32
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch x86
+                ; add DWORD [ecx + disp], x as i32
+                ; adc DWORD [ecx + disp + 4], 0
+            ),
+        }
+    }
+
+    fn gen_x32_sub(ops: &mut Assembler<X86Relocation>, cell_type: VMCellType, x: u32, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch x86
+                ; sub BYTE [ecx + disp], x as i8     // ptr[disp] -= x
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch x86
+                ; sub WORD [ecx + disp], x as i16
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch x86
+                ; sub DWORD [ecx + disp], x as i32
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch x86
+                ; sub DWORD [ecx + disp], x as i32
+                ; sbb DWORD [ecx + disp + 4], 0
+            ),
+        }
+    }
+
+    fn gen_x32_set_zero(ops: &mut Assembler<X86Relocation>, cell_type: VMCellType, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch x86
+                ; mov BYTE [ecx + disp], 0     // ptr[disp] = 0
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch x86
+                ; mov WORD [ecx + disp], 0
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch x86
+                ; mov DWORD [ecx + disp], 0
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch x86
+                ; mov DWORD [ecx + disp], 0
+                ; mov DWORD [ecx + disp + 4], 0
+            ),
+        }
+    }
+
+    /// x32 version of `gen_x64_mul_add`, `ebp` is free outside of calls.
+    fn gen_x32_mul_add(
+        ops: &mut Assembler<X86Relocation>,
+        cell_type: VMCellType,
+        disp: i32,
+        step: i64,
+        factor: i64,
+    ) {
+        let step = match i32::try_from(step) {
+            Ok(x) => x,
+            Err(_) => {
+                // can't be inside a 32-bit address space
+                gen_x32_cmp_zero(ops, cell_type, disp);
+                if step < 0 {
+                    dynasm!(ops
+                        ; .arch x86
+                        ; jnz  ->underflow
+                    );
+                } else {
+                    dynasm!(ops
+                        ; .arch x86
+                        ; jnz  ->overflow
+                    );
+                }
+                return;
+            }
+        };
+        gen_x32_cmp_zero(ops, cell_type, disp);
+        dynasm!(ops
+            ; .arch x86
+            ; jz   >skip            // skip if ptr[base] == 0
+            ; lea  edx, [ecx + step] // edx = ptr + base + offset
+            ; cmp  edx, esi
+            ; jb   ->underflow      // jmp if ptr + base + offset < memory_start
+            ; cmp  edx, edi
+            ; jnb  ->overflow       // jmp if ptr + base + offset >= memory_end
+        );
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch x86
+                ; movzx eax, BYTE [ecx + disp]
+                ; imul eax, eax, factor as i32
+                ; add  [edx], al
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch x86
+                ; movzx eax, WORD [ecx + disp]
+                ; imul eax, eax, factor as i32
+                ; add  [edx], ax
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch x86
+                ; mov  eax, [ecx + disp]
+                ; imul eax, eax, factor as i32
+                ; add  [edx], eax
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch x86
+                ; push edx
+                ; mov  eax, [ecx + disp]
+                ; mov  edx, factor as i32
+                ; mul  edx              // edx:eax = lo * factor_lo
+                ; mov  ebp, edx
+                ; imul edx, [ecx + disp], (factor >> 32) as i32
+                ; add  ebp, edx         // + lo * factor_hi
+                ; imul edx, [ecx + disp + 4], factor as i32
+                ; add  ebp, edx         // + hi * factor_lo
+                ; pop  edx
+                ; add  [edx], eax
+                ; adc  [edx + 4], ebp
+            ),
+        }
+        dynasm!(ops
+            ; .arch x86
+            ; skip:
+        );
+    }
+
+    fn gen_x32_cmp_zero(ops: &mut Assembler<X86Relocation>, cell_type: VMCellType, disp: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch x86
+                ; cmp BYTE [ecx + disp], 0
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch x86
+                ; cmp WORD [ecx + disp], 0
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch x86
+                ; cmp DWORD [ecx + disp], 0
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch x86
+                ; mov eax, [ecx + disp]
+                ; or  eax, [ecx + disp + 4]
+            ),
+        }
+    }
+
+    /// x32 version of `gen_x64_bounds`, the x32 backend can't grow.
+    fn gen_x32_bounds(
+        ops: &mut Assembler<X86Relocation>,
+        cell_type: VMCellType,
+        min: i64,
+        max: i64,
+    ) {
+        let (lo, hi) = match (cell_disp(cell_type, min), cell_disp(cell_type, max)) {
+            (Some(lo), Some(hi)) => (lo, hi),
+            (None, _) => {
+                dynasm!(ops
+                    ; .arch x86
+                    ; jmp  ->underflow
+                );
+                return;
             }
             _ => {
-                return Err(bferror::error::RuntimeError {
-                    index: 1,
-                    kind: bferror::error::RuntimeErrorKind::Unknown,
-                })
+                dynasm!(ops
+                    ; .arch x86
+                    ; jmp  ->overflow
+                );
+                return;
+            }
+        };
+        if lo < 0 {
+            dynasm!(ops
+                ; .arch x86
+                ; mov  eax, ecx
+                ; sub  eax, esi
+                ; cmp  eax, -lo
+                ; jb   ->underflow      // jmp if ptr + min < memory_start
+            );
+        }
+        if hi > 0 {
+            dynasm!(ops
+                ; .arch x86
+                ; mov  eax, edi
+                ; sub  eax, ecx
+                ; cmp  eax, hi
+                ; jbe  ->overflow       // jmp if ptr + max >= memory_end
+            );
+        }
+    }
+
+    fn gen_x32_code_normal(
+        irs: &Vec<IRNode>,
+        mut ops: Box<Assembler<X86Relocation>>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Box<Assembler<X86Relocation>> {
+        let cell_type = options.cell_type;
+        let cell_bytes = cell_type.bytes() as i64;
+        let disp = |offset: i64| cell_disp(cell_type, offset).unwrap_or(0);
+        let mut index = 0;
+        let mut block_end = 0;
+        let len = irs.len();
+        while index < len {
+            let ir_index = map.mark(ops.offset(), irs[index].sources());
+            if index >= block_end && !matches!(irs[index].ir, BFIR::Loop(_) | BFIR::Scan { .. }) {
+                let block = scan_block(irs, index, options);
+                gen_x32_bounds(&mut ops, cell_type, block.min, block.max);
+                block_end = block.end;
+            }
+            match &irs[index].ir {
+                BFIR::Add(x, o) => {
+                    index += 1;
+                    gen_x32_add(&mut ops, cell_type, *x, disp(*o));
+                }
+                BFIR::Sub(x, o) => {
+                    index += 1;
+                    gen_x32_sub(&mut ops, cell_type, *x, disp(*o));
+                }
+                BFIR::SetZero(o) => {
+                    index += 1;
+                    gen_x32_set_zero(&mut ops, cell_type, disp(*o));
+                }
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => {
+                    index += 1;
+                    let step = (base + offset).wrapping_mul(cell_bytes);
+                    gen_x32_mul_add(&mut ops, cell_type, disp(*base), step, *factor);
+                }
+                // the target was checked with the block
+                BFIR::MoveLeft(x) => {
+                    index += 1;
+                    let step = disp(*x as i64);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; sub ecx, step         // ptr -= x
+                    );
+                }
+                BFIR::MoveRight(x) => {
+                    index += 1;
+                    let step = disp(*x as i64);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; add ecx, step         // ptr += x
+                    );
+                }
+                BFIR::Input(o) => {
+                    index += 1;
+                    let o = disp(*o);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; mov  ebp, ecx         // save ptr
+                        ; lea  eax, [ecx + o]
+                        ; push eax
+                        ; push ebx              // arg0: this, arg1: ptr + offset
+                        ; call ->put_byte       // getbyte(this, ptr + offset)
+                        ; add  esp, 8
+                        ; test eax, eax
+                        ; jnz  ->io_error       // jmp if eax != 0
+                        ; mov  ecx, ebp         // recover ptr
+                    )
+                }
+                BFIR::Output(o) => {
+                    index += 1;
+                    let o = disp(*o);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; mov  ebp, ecx         // save ptr
+                        ; lea  eax, [ecx + o]
+                        ; push eax
+                        ; push ebx              // arg0: this, arg1: ptr + offset
+                        ; call ->get_byte       // putbyte(this, ptr + offset)
+                        ; add  esp, 8
+                        ; test eax, eax
+                        ; jnz  ->io_error       // jmp if eax != 0
+                        ; mov  ecx, ebp         // recover ptr
+                    )
+                }
+                BFIR::Scan { stride } => {
+                    index += 1;
+                    let step = cell_disp(cell_type, *stride).unwrap_or(i32::MAX);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; again:
+                    );
+                    gen_x32_cmp_zero(&mut ops, cell_type, 0);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; jz   >done            // stop if *ptr == 0
+                    );
+                    if step < 0 {
+                        dynasm!(ops
+                            ; .arch x86
+                            ; mov  eax, ecx
+                            ; sub  eax, esi
+                            ; cmp  eax, -step
+                            ; jb   ->underflow      // jmp if ptr + step < memory_start
+                        );
+                    } else {
+                        dynasm!(ops
+                            ; .arch x86
+                            ; mov  eax, edi
+                            ; sub  eax, ecx
+                            ; cmp  eax, step
+                            ; jbe  ->overflow       // jmp if ptr + step >= memory_end
+                        );
+                    }
+                    dynasm!(ops
+                        ; .arch x86
+                        ; add  ecx, step
+                        ; jmp  <again
+                        ; done:
+                    );
+                }
+                BFIR::Loop(x) => {
+                    index += 1;
+                    let left = ops.new_dynamic_label();
+                    let right = ops.new_dynamic_label();
+                    gen_x32_cmp_zero(&mut ops, cell_type, 0);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; jz => right       // jmp if *ptr == 0
+                        ; => left
+                    );
+                    ops = gen_x32_code_normal(&x.borrow(), ops, options, map);
+                    map.marks.push((ops.offset().0, ir_index));
+                    gen_x32_cmp_zero(&mut ops, cell_type, 0);
+                    dynasm!(ops
+                        ; .arch x86
+                        ; jnz => left       // jmp if *ptr != 0
+                        ; => right
+                    );
+                }
             }
         }
+        return ops;
+    }
+
+    #[cfg(target_arch = "x86")]
+    fn gen_x32_callbacks(
+        mut ops: Box<Assembler<X86Relocation>>,
+    ) -> Result<Box<Assembler<X86Relocation>>, bferror::error::RuntimeError> {
+        dynasm!(ops
+            ; .arch x86
+            ; -> put_byte:
+            ; mov eax, DWORD vm::VMStruct::put_x32_byte as *const () as i32
+            ; jmp eax
+            ; -> get_byte:
+            ; mov eax, DWORD vm::VMStruct::get_x32_byte as *const () as i32
+            ; jmp eax
+            ; -> overflow_error:
+            ; mov eax, DWORD vm::VMStruct::overflow_x32_error as *const () as i32
+            ; jmp eax
+        );
+        return Ok(ops);
+    }
+
+    #[cfg(not(target_arch = "x86"))]
+    fn gen_x32_callbacks(
+        _ops: Box<Assembler<X86Relocation>>,
+    ) -> Result<Box<Assembler<X86Relocation>>, bferror::error::RuntimeError> {
+        return Err(vm::wrong_host(&VMArchType::X32));
+    }
+
+    /// Store `eax` into the cell at `edx`, `hi` being the upper half of 64-bit cells.
+    fn gen_x32_store(ops: &mut Assembler<X86Relocation>, cell_type: VMCellType, hi: i32) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch x86
+                ; mov [edx], al
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch x86
+                ; mov [edx], ax
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch x86
+                ; mov [edx], eax
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch x86
+                ; mov [edx], eax
+                ; mov DWORD [edx + 4], hi
+            ),
+        }
+    }
+
+    fn gen_x32_syscalls(
+        mut ops: Box<Assembler<X86Relocation>>,
+        options: &Options,
+    ) -> Box<Assembler<X86Relocation>> {
+        let cell_type = options.cell_type;
+        // cdecl stubs returning 0 on success, error code otherwise
+        dynasm!(ops
+            ; .arch x86
+            ; -> put_byte:
+            ; push ebx
+            ; push 0                    // byte buffer
+            ; mov  eax, 3               // read(0, buf, 1)
+            ; xor  ebx, ebx
+            ; mov  ecx, esp
+            ; mov  edx, 1
+            ; int  BYTE 0x80_u8 as i8
+            ; mov  edx, [esp + 16]      // ptr
+            ; cmp  eax, 1
+            ; jne  >eof
+            ; movzx eax, BYTE [esp]
+        );
+        gen_x32_store(&mut ops, cell_type, 0);
+        dynasm!(ops
+            ; .arch x86
+            ; jmp  >done
+            ; eof:
+            ; test eax, eax
+            ; jnz  >fail                // jmp if read failed
+        );
+        match options.eof_type {
+            VMEofType::Error => dynasm!(ops
+                ; .arch x86
+                ; jmp  >fail
+            ),
+            VMEofType::Zero => {
+                dynasm!(ops
+                    ; .arch x86
+                    ; xor  eax, eax
+                );
+                gen_x32_store(&mut ops, cell_type, 0);
+            }
+            VMEofType::MinusOne => {
+                dynasm!(ops
+                    ; .arch x86
+                    ; mov  eax, -1
+                );
+                gen_x32_store(&mut ops, cell_type, -1);
+            }
+            VMEofType::Unchanged => (),
+        }
+        dynasm!(ops
+            ; .arch x86
+            ; done:
+            ; xor  eax, eax
+            ; jmp  >exit
+            ; fail:
+            ; mov  eax, 1
+            ; exit:
+            ; add  esp, 4
+            ; pop  ebx
+            ; ret
+            ; -> get_byte:
+            ; push ebx
+            ; mov  eax, 4               // write(1, ptr, 1)
+            ; mov  ebx, 1
+            ; mov  ecx, [esp + 12]
+            ; mov  edx, 1
+            ; int  BYTE 0x80_u8 as i8
+            ; pop  ebx
+            ; sub  eax, 1
+            ; setnz al
+            ; movzx eax, al
+            ; ret
+            ; -> overflow_error:
+            ; mov  eax, 2
+            ; ret
+        );
+        return ops;
+    }
+
+    fn gen_x32_function(
+        irs: &Vec<IRNode>,
+        mut ops: Box<Assembler<X86Relocation>>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Box<Assembler<X86Relocation>> {
+        // cdecl: fn(this, memory_start, memory_end) -> error
+        dynasm!(ops
+            ; .arch x86
+            ; -> main:
+            ; push ebx                  // save callee-saved registers
+            ; push esi
+            ; push edi
+            ; push ebp
+            // entered with esp = 12 mod 16, so that esp is 16 bytes aligned
+            // again once the two arguments of the callbacks are pushed
+            ; sub  esp, 4
+            ; mov  ebx, [esp + 24]      // save this
+            ; mov  esi, [esp + 28]      // save memory_start
+            ; mov  edi, [esp + 32]      // save memory_end
+            ; mov  ecx, esi             // ptr = memory_start
+        );
+        ops = gen_x32_code_normal(irs, ops, options, map);
+        dynasm!(ops
+            ; .arch x86
+            ; xor eax, eax
+            ; jmp >exit
+            ; -> underflow:
+            ; mov  eax, 1               // below = true
+            ; jmp  >fail
+            ; -> overflow:
+            ; xor  eax, eax
+            ; fail:
+            ; sub  esp, 4
+            ; push eax
+            ; call ->overflow_error
+            ; add  esp, 8
+            ; jmp >exit
+            ; -> io_error:
+            ; exit:
+            ; add esp, 4
+            ; pop ebp
+            ; pop edi
+            ; pop esi
+            ; pop ebx
+            ; ret
+        );
+        return ops;
+    }
+
+    fn new_x32_assembler() -> Result<Box<Assembler<X86Relocation>>, bferror::error::RuntimeError> {
+        let ops = dynasmrt::x86::Assembler::new();
+        if ops.is_err() {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        return Ok(Box::new(ops.unwrap()));
+    }
+
+    fn unsupported_x32(options: &Options) -> Result<(), bferror::error::RuntimeError> {
+        let what = if options.growable {
+            "growable tape is not supported by the x32 backend"
+        } else if options.checked_cells {
+            "checked cells are not supported by the x32 backend"
+        } else {
+            return Ok(());
+        };
+        return Err(bferror::error::RuntimeError {
+            index: 1,
+            kind: bferror::error::RuntimeErrorKind::Unsupported(what),
+            span: None,
+        });
+    }
+
+    fn gen_x32_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<dynasmrt::ExecutableBuffer, bferror::error::RuntimeError> {
+        unsupported_x32(options)?;
+        let mut ops_ptr = new_x32_assembler()?;
+        ops_ptr = gen_x32_function(irs, ops_ptr, options, map);
+        ops_ptr = gen_x32_callbacks(ops_ptr)?;
+        return finalize(*ops_ptr);
+    }
+
+    /// Code of the standalone 32-bit executable, loaded at `ELF32_ENTRY_ADDR`
+    /// and entered at its first byte, with the size of its memory.
+    pub fn gen_x32_elf_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<(dynasmrt::ExecutableBuffer, usize), bferror::error::RuntimeError> {
+        unsupported_x32(options)?;
+        let memory_size = vm::tape_bytes(options)?;
+        if memory_size == 0 || memory_size > elf::ELF32_MEMORY_MAX {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        let memory_start = elf::ELF32_MEMORY_ADDR as i32;
+        let memory_end = (elf::ELF32_MEMORY_ADDR as usize + memory_size) as i32;
+        let mut ops_ptr = new_x32_assembler()?;
+        dynasm!(ops_ptr
+            ; .arch x86
+            // esp is 16 bytes aligned at the entry point, keep it aligned
+            // at the call once the three arguments are pushed
+            ; sub  esp, 4
+            ; push DWORD memory_end
+            ; push DWORD memory_start
+            ; push 0                    // no VMStruct
+            ; call ->main
+            ; mov  ebx, eax             // exit(error)
+            ; mov  eax, 1
+            ; int  BYTE 0x80_u8 as i8
+        );
+        ops_ptr = gen_x32_function(irs, ops_ptr, options, map);
+        ops_ptr = gen_x32_syscalls(ops_ptr, options);
+        return Ok((finalize(*ops_ptr)?, memory_size));
+    }
+
+    /// Generate a standalone 32-bit Linux ELF executable running `irs` on a
+    /// tape of `options.tape_size` cells.
+    ///
+    /// The exit status is 0 on success, 1 on an IO error and 2 when the
+    /// pointer leaves the memory.
+    pub fn gen_x32_elf(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<Vec<u8>, bferror::error::RuntimeError> {
+        let (code, memory_size) = gen_x32_elf_code(irs, options, &mut CodeMap::default())?;
+        return Ok(elf::write_elf32(&code, memory_size));
+    }
+
+    /// Load `value` into `X(reg)`, with `movn` for negative values.
+    fn gen_aarch64_mov(ops: &mut Assembler<Aarch64Relocation>, reg: u32, value: u64) {
+        let part = |shift: u32| ((value >> shift) & 0xffff) as u32;
+        // chunks equal to the fill of the first instruction are skipped
+        let fill = if (value as i64) < 0 {
+            dynasm!(ops
+                ; .arch aarch64
+                ; movn X(reg), !part(0) & 0xffff
+            );
+            0xffff
+        } else {
+            dynasm!(ops
+                ; .arch aarch64
+                ; movz X(reg), part(0)
+            );
+            0
+        };
+        if part(16) != fill {
+            dynasm!(ops
+                ; .arch aarch64
+                ; movk X(reg), part(16), lsl 16
+            );
+        }
+        if part(32) != fill {
+            dynasm!(ops
+                ; .arch aarch64
+                ; movk X(reg), part(32), lsl 32
+            );
+        }
+        if part(48) != fill {
+            dynasm!(ops
+                ; .arch aarch64
+                ; movk X(reg), part(48), lsl 48
+            );
+        }
+    }
+
+    /// Emit `X(dst) = X(src) + value`, `value` being in bytes.
+    fn gen_aarch64_add_imm(ops: &mut Assembler<Aarch64Relocation>, dst: u32, src: u32, value: i64) {
+        match value {
+            0..=0xfff => dynasm!(ops
+                ; .arch aarch64
+                ; add XSP(dst), XSP(src), value as u32
+            ),
+            -0xfff..=-1 => dynasm!(ops
+                ; .arch aarch64
+                ; sub XSP(dst), XSP(src), -value as u32
+            ),
+            _ => {
+                gen_aarch64_mov(ops, 10, value as u64);
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; add X(dst), X(src), x10
+                );
+            }
+        }
+    }
+
+    /// Load the cell at `[X(base)]` into `X(reg)`, zero-extended.
+    fn gen_aarch64_load(
+        ops: &mut Assembler<Aarch64Relocation>,
+        cell_type: VMCellType,
+        reg: u32,
+        base: u32,
+    ) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch aarch64
+                ; ldrb W(reg), [X(base)]
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch aarch64
+                ; ldrh W(reg), [X(base)]
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch aarch64
+                ; ldr W(reg), [X(base)]
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch aarch64
+                ; ldr X(reg), [X(base)]
+            ),
+        }
+    }
+
+    /// Store `X(reg)` into the cell at `[X(base)]`.
+    fn gen_aarch64_store(
+        ops: &mut Assembler<Aarch64Relocation>,
+        cell_type: VMCellType,
+        reg: u32,
+        base: u32,
+    ) {
+        match cell_type {
+            VMCellType::U8 => dynasm!(ops
+                ; .arch aarch64
+                ; strb W(reg), [X(base)]
+            ),
+            VMCellType::U16 => dynasm!(ops
+                ; .arch aarch64
+                ; strh W(reg), [X(base)]
+            ),
+            VMCellType::U32 => dynasm!(ops
+                ; .arch aarch64
+                ; str W(reg), [X(base)]
+            ),
+            VMCellType::U64 => dynasm!(ops
+                ; .arch aarch64
+                ; str X(reg), [X(base)]
+            ),
+        }
+    }
+
+    /// Emit `ptr[disp] += x` or `ptr[disp] -= x`, raising `CellOverflow`
+    /// for instruction `ir_index` on a carry in checked mode.
+    fn gen_aarch64_add(
+        ops: &mut Assembler<Aarch64Relocation>,
+        options: &Options,
+        x: u32,
+        disp: i64,
+        sub: bool,
+        ir_index: usize,
+    ) {
+        let cell_type = options.cell_type;
+        gen_aarch64_add_imm(ops, 9, 22, disp);
+        gen_aarch64_load(ops, cell_type, 11, 9);
+        gen_aarch64_mov(ops, 12, x as u64);
+        match (sub, options.checked_cells) {
+            (false, false) => dynasm!(ops
+                ; .arch aarch64
+                ; add  x11, x11, x12        // ptr[disp] += x
+            ),
+            (true, false) => dynasm!(ops
+                ; .arch aarch64
+                ; sub  x11, x11, x12        // ptr[disp] -= x
+            ),
+            (true, true) => dynasm!(ops
+                ; .arch aarch64
+                ; subs x11, x11, x12
+                ; b.hs >ok                  // jmp if no borrow
+            ),
+            (false, true) => match cell_type {
+                VMCellType::U8 => dynasm!(ops
+                    ; .arch aarch64
+                    ; add  x11, x11, x12
+                    ; lsr  x13, x11, 8
+                    ; cbz  x13, >ok             // jmp if no carry
+                ),
+                VMCellType::U16 => dynasm!(ops
+                    ; .arch aarch64
+                    ; add  x11, x11, x12
+                    ; lsr  x13, x11, 16
+                    ; cbz  x13, >ok
+                ),
+                VMCellType::U32 => dynasm!(ops
+                    ; .arch aarch64
+                    ; add  x11, x11, x12
+                    ; lsr  x13, x11, 32
+                    ; cbz  x13, >ok
+                ),
+                VMCellType::U64 => dynasm!(ops
+                    ; .arch aarch64
+                    ; adds x11, x11, x12
+                    ; b.lo >ok
+                ),
+            },
+        }
+        if options.checked_cells {
+            gen_aarch64_mov(ops, 0, ir_index as u64);
+            dynasm!(ops
+                ; .arch aarch64
+                ; bl   ->cell_overflow      // cell_overflow(index)
+                ; b    ->io_error
+                ; ok:
+            );
+        }
+        gen_aarch64_store(ops, cell_type, 11, 9);
+    }
+
+    /// AArch64 version of `gen_x64_mul_add`.
+    fn gen_aarch64_mul_add(
+        ops: &mut Assembler<Aarch64Relocation>,
+        options: &Options,
+        disp: i64,
+        step: i64,
+        factor: i64,
+    ) {
+        let cell_type = options.cell_type;
+        dynasm!(ops
+            ; .arch aarch64
+            ; again:
+        );
+        gen_aarch64_add_imm(ops, 9, 22, disp);
+        gen_aarch64_load(ops, cell_type, 11, 9);
+        dynasm!(ops
+            ; .arch aarch64
+            ; cbz  x11, >skip               // skip if ptr[base] == 0
+        );
+        gen_aarch64_mov(ops, 12, factor as u64);
+        dynasm!(ops
+            ; .arch aarch64
+            ; mul  x11, x11, x12            // x11 = ptr[base] * factor
+        );
+        gen_aarch64_add_imm(ops, 9, 22, step);
+        if options.growable {
+            dynasm!(ops
+                ; .arch aarch64
+                ; cmp  x9, x20
+                ; b.lo >grow
+                ; cmp  x9, x21
+                ; b.lo >done
+                ; grow:
+                ; mov  x22, x9              // grow around ptr + offset
+                ; bl   ->grow
+            );
+            gen_aarch64_add_imm(ops, 22, 22, step.wrapping_neg());
+            dynasm!(ops
+                ; .arch aarch64
+                ; b    <again
+                ; done:
+            );
+        } else {
+            dynasm!(ops
+                ; .arch aarch64
+                ; cmp  x9, x20
+                ; b.lo >outside             // jmp if ptr + offset < memory_start
+                ; cmp  x9, x21
+                ; b.lo >inside              // ok if ptr + offset < memory_end
+                ; outside:
+            );
+            // the pointer is inside, so only one end can be crossed
+            if step < 0 {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; bl   ->underflow
+                );
+            } else {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; bl   ->overflow
+                );
+            }
+            dynasm!(ops
+                ; .arch aarch64
+                ; inside:
+            );
+        }
+        gen_aarch64_load(ops, cell_type, 12, 9);
+        dynasm!(ops
+            ; .arch aarch64
+            ; add  x12, x12, x11
+        );
+        gen_aarch64_store(ops, cell_type, 12, 9);
+        dynasm!(ops
+            ; .arch aarch64
+            ; skip:
+        );
+    }
+
+    /// AArch64 version of `gen_x64_bounds`.
+    fn gen_aarch64_bounds(
+        ops: &mut Assembler<Aarch64Relocation>,
+        options: &Options,
+        block: &Block,
+        position: usize,
+        map: &mut CodeMap,
+    ) {
+        let (lo, hi) = match (
+            cell_disp(options.cell_type, block.min),
+            cell_disp(options.cell_type, block.max),
+        ) {
+            (Some(lo), Some(hi)) => (lo as i64, hi as i64),
+            (lo, _) => {
+                let at = if lo.is_none() {
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; bl   ->underflow
+                    );
+                    block.min_at
+                } else {
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; bl   ->overflow
+                    );
+                    block.max_at
+                };
+                map.check(ops.offset(), position + at);
+                return;
+            }
+        };
+        dynasm!(ops
+            ; .arch aarch64
+            ; again:
+        );
+        if lo < 0 {
+            gen_aarch64_mov(ops, 10, -lo as u64);
+            dynasm!(ops
+                ; .arch aarch64
+                ; sub  x9, x22, x20
+                ; cmp  x9, x10
+                ; b.hs >low                 // ok if ptr + min >= memory_start
+            );
+            if options.growable {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; sub  x22, x22, x10        // grow around ptr + min
+                    ; bl   ->grow
+                );
+                map.check(ops.offset(), position + block.min_at);
+                gen_aarch64_add_imm(ops, 22, 22, -lo);
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; b    <again
+                );
+            } else {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; bl   ->underflow
+                );
+                map.check(ops.offset(), position + block.min_at);
+            }
+            dynasm!(ops
+                ; .arch aarch64
+                ; low:
+            );
+        }
+        if hi > 0 {
+            gen_aarch64_mov(ops, 10, hi as u64);
+            dynasm!(ops
+                ; .arch aarch64
+                ; sub  x9, x21, x22
+                ; cmp  x9, x10
+                ; b.hi >high                // ok if ptr + max < memory_end
+            );
+            if options.growable {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; add  x22, x22, x10        // grow around ptr + max
+                    ; bl   ->grow
+                );
+                map.check(ops.offset(), position + block.max_at);
+                gen_aarch64_add_imm(ops, 22, 22, -hi);
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; b    <again
+                );
+            } else {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; bl   ->overflow
+                );
+                map.check(ops.offset(), position + block.max_at);
+            }
+            dynasm!(ops
+                ; .arch aarch64
+                ; high:
+            );
+        }
+    }
+
+    /// AArch64 version of `gen_x64_code_normal`. `x19` holds `this`, `x20`
+    /// and `x21` the memory bounds and `x22` the pointer, all callee-saved so
+    /// that they survive the callbacks. `x9` to `x13` are scratch registers.
+    fn gen_aarch64_code_normal(
+        irs: &Vec<IRNode>,
+        mut ops: Box<Assembler<Aarch64Relocation>>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Box<Assembler<Aarch64Relocation>> {
+        let cell_type = options.cell_type;
+        let cell_bytes = cell_type.bytes() as i64;
+        // out of range displacements already fail in the block check
+        let disp = |offset: i64| cell_disp(cell_type, offset).unwrap_or(0) as i64;
+        let mut index = 0;
+        let mut block_end = 0;
+        let len = irs.len();
+        while index < len {
+            let ir_index = map.mark(ops.offset(), irs[index].sources());
+            if index >= block_end && !matches!(irs[index].ir, BFIR::Loop(_) | BFIR::Scan { .. }) {
+                let block = scan_block(irs, index, options);
+                gen_aarch64_bounds(&mut ops, options, &block, ir_index, map);
+                block_end = block.end;
+            }
+            match &irs[index].ir {
+                BFIR::Add(x, o) => {
+                    index += 1;
+                    gen_aarch64_add(&mut ops, options, *x, disp(*o), false, ir_index);
+                }
+                BFIR::Sub(x, o) => {
+                    index += 1;
+                    gen_aarch64_add(&mut ops, options, *x, disp(*o), true, ir_index);
+                }
+                BFIR::SetZero(o) => {
+                    index += 1;
+                    gen_aarch64_add_imm(&mut ops, 9, 22, disp(*o));
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; movz x11, 0               // ptr[disp] = 0
+                    );
+                    gen_aarch64_store(&mut ops, cell_type, 11, 9);
+                }
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => {
+                    index += 1;
+                    let step = (base + offset).wrapping_mul(cell_bytes);
+                    gen_aarch64_mul_add(&mut ops, options, disp(*base), step, *factor);
+                }
+                // the target was checked with the block
+                BFIR::MoveLeft(x) => {
+                    index += 1;
+                    gen_aarch64_add_imm(&mut ops, 22, 22, -(*x as i64) * cell_bytes);
+                }
+                BFIR::MoveRight(x) => {
+                    index += 1;
+                    gen_aarch64_add_imm(&mut ops, 22, 22, *x as i64 * cell_bytes);
+                }
+                BFIR::Input(o) => {
+                    index += 1;
+                    gen_aarch64_add_imm(&mut ops, 1, 22, disp(*o));
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; mov  x0, x19              // arg0: this, arg1: ptr + offset
+                        ; bl   ->put_byte           // getbyte(this, ptr + offset)
+                        ; cbnz x0, ->io_error       // jmp if x0 != 0
+                    )
+                }
+                BFIR::Output(o) => {
+                    index += 1;
+                    gen_aarch64_add_imm(&mut ops, 1, 22, disp(*o));
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; mov  x0, x19              // arg0: this, arg1: ptr + offset
+                        ; bl   ->get_byte           // putbyte(this, ptr + offset)
+                        ; cbnz x0, ->io_error       // jmp if x0 != 0
+                    )
+                }
+                BFIR::Scan { stride } => {
+                    index += 1;
+                    gen_aarch64_load(&mut ops, cell_type, 11, 22);
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; cbz  x11, >done           // nothing to do if *ptr == 0
+                    );
+                    gen_aarch64_mov(&mut ops, 2, stride.wrapping_mul(cell_bytes) as u64);
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; bl   ->scan               // scan(ptr, step)
+                        ; cbnz x0, ->io_error       // jmp if x0 != 0
+                        ; done:
+                    );
+                }
+                BFIR::Loop(x) => {
+                    index += 1;
+                    let left = ops.new_dynamic_label();
+                    let right = ops.new_dynamic_label();
+                    gen_aarch64_load(&mut ops, cell_type, 11, 22);
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; cbz  x11, => right        // jmp if *ptr == 0
+                        ; => left
+                    );
+                    ops = gen_aarch64_code_normal(&x.borrow(), ops, options, map);
+                    map.marks.push((ops.offset().0, ir_index));
+                    gen_aarch64_load(&mut ops, cell_type, 11, 22);
+                    dynasm!(ops
+                        ; .arch aarch64
+                        ; cbnz x11, => left         // jmp if *ptr != 0
+                        ; => right
+                    );
+                }
+            }
+        }
+        return ops;
+    }
+
+    fn gen_aarch64_function(
+        irs: &Vec<IRNode>,
+        mut ops: Box<Assembler<Aarch64Relocation>>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Box<Assembler<Aarch64Relocation>> {
+        // aapcs64: fn(this, memory_start, memory_end) -> error
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> main:
+            ; stp  x29, x30, [sp, #-48]!   // save frame and callee-saved registers
+            ; mov  x29, sp
+            ; stp  x19, x20, [sp, #16]
+            ; stp  x21, x22, [sp, #32]
+            ; mov  x19, x0                  // save this
+            ; mov  x20, x1                  // save memory_start
+            ; mov  x21, x2                  // save memory_end
+            ; mov  x22, x1                  // ptr = memory_start
+        );
+        ops = gen_aarch64_code_normal(irs, ops, options, map);
+        dynasm!(ops
+            ; .arch aarch64
+            ; movz x0, 0
+            ; b    >exit
+            ; -> underflow:                 // called by the failing check
+            ; movz x1, 1                    // below = true
+            ; b    >fail
+            ; -> overflow:
+            ; movz x1, 0
+            ; fail:
+            ; mov  x0, x30                  // arg0: return address of the call
+            ; bl   ->overflow_error
+            ; b    >exit
+            ; -> io_error:
+            ; exit:
+            ; ldp  x21, x22, [sp, #32]
+            ; ldp  x19, x20, [sp, #16]
+            ; ldp  x29, x30, [sp], #48
+            ; ret
+        );
+        return ops;
+    }
+
+    /// Jump to the host function at `addr`, keeping the return address.
+    #[cfg(target_arch = "aarch64")]
+    fn gen_aarch64_jump(ops: &mut Assembler<Aarch64Relocation>, addr: *const ()) {
+        gen_aarch64_mov(ops, 16, addr as u64);
+        dynasm!(ops
+            ; .arch aarch64
+            ; br   x16
+        );
+    }
+
+    /// Subroutines calling back into `VMStruct`, only usable on an aarch64 host.
+    /// `->scan` and `->grow` reload `memory_start`, `memory_end` and `ptr`.
+    /// Like on x64 they save the return address to `VMStruct::fault`.
+    #[cfg(target_arch = "aarch64")]
+    fn gen_aarch64_callbacks(
+        mut ops: Box<Assembler<Aarch64Relocation>>,
+        options: &Options,
+    ) -> Result<Box<Assembler<Aarch64Relocation>>, bferror::error::RuntimeError> {
+        let (start, end, ptr) = vm::tape_offsets();
+        let (start, end, ptr) = (start as u32, end as u32, ptr as u32);
+        let fault = vm::fault_offset() as u32;
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> put_byte:
+            ; str  x30, [x19, fault]
+        );
+        gen_aarch64_jump(&mut ops, vm::VMStruct::put_aarch64_byte as *const ());
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> get_byte:
+            ; str  x30, [x19, fault]
+        );
+        gen_aarch64_jump(&mut ops, vm::VMStruct::get_aarch64_byte as *const ());
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> overflow_error:
+            ; str  x0, [x19, fault]         // return address of the call to ->overflow
+            ; mov  x0, x1
+        );
+        gen_aarch64_jump(&mut ops, vm::VMStruct::overflow_aarch64_error as *const ());
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> cell_overflow:
+            ; str  x30, [x19, fault]
+        );
+        gen_aarch64_jump(&mut ops, vm::VMStruct::cell_overflow_aarch64 as *const ());
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> scan:
+            ; str  x30, [x19, fault]
+            ; stp  x29, x30, [sp, #-16]!
+            ; mov  x0, x19
+            ; sub  x1, x22, x20             // arg0: this, arg1: ptr - memory_start, arg2: step
+        );
+        gen_aarch64_mov(&mut ops, 16, vm::VMStruct::scan_aarch64 as *const () as u64);
+        dynasm!(ops
+            ; .arch aarch64
+            ; blr  x16                      // scan(this, offset, step)
+            ; ldp  x29, x30, [sp], #16
+            ; ldr  x20, [x19, start]
+            ; ldr  x21, [x19, end]
+            ; ldr  x22, [x19, ptr]
+            ; ret
+        );
+        if options.growable {
+            // grow the memory, then reload memory_start, memory_end and ptr
+            dynasm!(ops
+                ; .arch aarch64
+                ; -> grow:
+                ; str  x30, [x19, fault]
+                ; stp  x29, x30, [sp, #-16]!
+                ; mov  x0, x19
+                ; sub  x1, x22, x20         // arg0: this, arg1: ptr - memory_start
+            );
+            gen_aarch64_mov(&mut ops, 16, vm::VMStruct::grow_aarch64 as *const () as u64);
+            dynasm!(ops
+                ; .arch aarch64
+                ; blr  x16                  // grow(this, offset)
+                ; ldp  x29, x30, [sp], #16
+                ; cbnz x0, ->io_error       // jmp if x0 != 0
+                ; ldr  x20, [x19, start]
+                ; ldr  x21, [x19, end]
+                ; ldr  x22, [x19, ptr]
+                ; ret
+            );
+        }
+        return Ok(ops);
+    }
+
+    #[cfg(not(target_arch = "aarch64"))]
+    fn gen_aarch64_callbacks(
+        _ops: Box<Assembler<Aarch64Relocation>>,
+        _options: &Options,
+    ) -> Result<Box<Assembler<Aarch64Relocation>>, bferror::error::RuntimeError> {
+        return Err(vm::wrong_host(&VMArchType::AArch64));
+    }
+
+    /// Linux `svc` versions of the callbacks for standalone executables,
+    /// returning 0 on success or the exit status otherwise.
+    fn gen_aarch64_syscalls(
+        mut ops: Box<Assembler<Aarch64Relocation>>,
+        options: &Options,
+    ) -> Box<Assembler<Aarch64Relocation>> {
+        let cell_type = options.cell_type;
+        dynasm!(ops
+            ; .arch aarch64
+            ; -> put_byte:
+            ; stp  x1, xzr, [sp, #-16]!     // ptr, byte buffer
+            ; movz x0, 0                    // read(0, buf, 1)
+            ; add  x1, sp, 8
+            ; movz x2, 1
+            ; movz x8, 63
+            ; svc  0
+            ; ldr  x9, [sp]
+            ; cmp  x0, 1
+            ; b.ne >eof
+            ; ldrb w11, [sp, 8]
+        );
+        gen_aarch64_store(&mut ops, cell_type, 11, 9);
+        dynasm!(ops
+            ; .arch aarch64
+            ; b    >done
+            ; eof:
+            ; cbnz x0, >fail                // jmp if read failed
+        );
+        match options.eof_type {
+            VMEofType::Error => dynasm!(ops
+                ; .arch aarch64
+                ; b    >fail
+            ),
+            VMEofType::Zero => {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; movz x11, 0
+                );
+                gen_aarch64_store(&mut ops, cell_type, 11, 9);
+            }
+            VMEofType::MinusOne => {
+                dynasm!(ops
+                    ; .arch aarch64
+                    ; movn x11, 0
+                );
+                gen_aarch64_store(&mut ops, cell_type, 11, 9);
+            }
+            VMEofType::Unchanged => (),
+        }
+        dynasm!(ops
+            ; .arch aarch64
+            ; done:
+            ; movz x0, 0
+            ; b    >exit
+            ; fail:
+            ; movz x0, 1
+            ; exit:
+            ; add  sp, sp, 16
+            ; ret
+            ; -> get_byte:
+            ; movz x0, 1                    // write(1, ptr, 1)
+            ; movz x2, 1
+            ; movz x8, 64
+            ; svc  0
+            ; cmp  x0, 1
+            ; cset x0, ne
+            ; ret
+            ; -> overflow_error:
+            ; movz x0, 2
+            ; ret
+            ; -> cell_overflow:
+            ; movz x0, 3
+            ; ret
+            ; -> scan:
+            ; again:
+        );
+        gen_aarch64_load(&mut ops, cell_type, 11, 22);
+        dynasm!(ops
+            ; .arch aarch64
+            ; cbz  x11, >found              // stop if *ptr == 0
+            ; add  x9, x22, x2
+            ; cmp  x9, x20
+            ; b.lo >fail                    // jmp if ptr + step < memory_start
+            ; cmp  x9, x21
+            ; b.hs >fail                    // jmp if ptr + step >= memory_end
+            ; mov  x22, x9
+            ; b    <again
+            ; found:
+            ; movz x0, 0
+            ; ret
+            ; fail:
+            ; movz x0, 2
+            ; ret
+        );
+        return ops;
+    }
+
+    fn new_aarch64_assembler(
+    ) -> Result<Box<Assembler<Aarch64Relocation>>, bferror::error::RuntimeError> {
+        let ops = dynasmrt::aarch64::Assembler::new();
+        if ops.is_err() {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        return Ok(Box::new(ops.unwrap()));
+    }
+
+    fn gen_aarch64_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<dynasmrt::ExecutableBuffer, bferror::error::RuntimeError> {
+        let mut ops_ptr = new_aarch64_assembler()?;
+        ops_ptr = gen_aarch64_function(irs, ops_ptr, options, map);
+        ops_ptr = gen_aarch64_callbacks(ops_ptr, options)?;
+        return finalize(*ops_ptr);
+    }
+
+    /// Code of the standalone aarch64 executable, loaded at `ELF64_ENTRY_ADDR`
+    /// and entered at its first byte, with the size of its memory.
+    pub fn gen_aarch64_elf_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<(dynasmrt::ExecutableBuffer, usize), bferror::error::RuntimeError> {
+        if options.growable {
+            // there is no runtime to grow the memory
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Unsupported(
+                    "growable tape is not supported by standalone executables",
+                ),
+                span: None,
+            });
+        }
+        let memory_size = vm::tape_bytes(options)?;
+        if memory_size == 0 || memory_size > elf::ELF64_MEMORY_MAX {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        let memory_start = elf::ELF64_MEMORY_ADDR;
+        let memory_end = memory_start + memory_size as u64;
+        let mut ops_ptr = new_aarch64_assembler()?;
+        dynasm!(ops_ptr
+            ; .arch aarch64
+            ; movz x0, 0                    // no VMStruct
+        );
+        gen_aarch64_mov(&mut ops_ptr, 1, memory_start);
+        gen_aarch64_mov(&mut ops_ptr, 2, memory_end);
+        dynasm!(ops_ptr
+            ; .arch aarch64
+            ; bl   ->main
+            ; movz x8, 93                   // exit(error)
+            ; svc  0
+        );
+        ops_ptr = gen_aarch64_function(irs, ops_ptr, options, map);
+        ops_ptr = gen_aarch64_syscalls(ops_ptr, options);
+        return Ok((finalize(*ops_ptr)?, memory_size));
+    }
+
+    /// Generate a standalone aarch64 Linux ELF executable running `irs` on
+    /// a tape of `options.tape_size` cells, with the exit statuses of
+    /// `gen_x64_elf`. It runs under qemu-user on other hosts.
+    pub fn gen_aarch64_elf(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<Vec<u8>, bferror::error::RuntimeError> {
+        let (code, memory_size) = gen_aarch64_elf_code(irs, options, &mut CodeMap::default())?;
+        return Ok(elf::write_elf64(&code, memory_size, elf::EM_AARCH64));
+    }
+
+    fn finalize<R: Relocation>(
+        ops: Assembler<R>,
+    ) -> Result<dynasmrt::ExecutableBuffer, bferror::error::RuntimeError> {
+        return ops.finalize().map_err(|_| bferror::error::RuntimeError {
+            index: 1,
+            kind: bferror::error::RuntimeErrorKind::Memory,
+            span: None,
+        });
+    }
+
+    pub fn gen_code(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<dynasmrt::ExecutableBuffer, bferror::error::RuntimeError> {
+        return gen_code_map(irs, options, &mut CodeMap::default());
+    }
+
+    /// `gen_code`, also recording where each instruction starts in `map`.
+    pub fn gen_code_map(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        map: &mut CodeMap,
+    ) -> Result<dynasmrt::ExecutableBuffer, bferror::error::RuntimeError> {
+        match options.vm_arch_type {
+            VMArchType::X64 => {
+                return gen_x64_code(irs, options, map);
+            }
+            VMArchType::X32 => {
+                return gen_x32_code(irs, options, map);
+            }
+            VMArchType::AArch64 => {
+                return gen_aarch64_code(irs, options, map);
+            }
+        }
+    }
+}
+
+pub mod cgen {
+    use crate::bfparser::frontend::ir::{IRNode, BFIR};
+    use crate::bftype::bfcate::bfcate::{VMCellType, VMEofType};
+    use crate::Options;
+
+    const C_RUNTIME: &str = r#"static cell *tape;
+static size_t size;
+static ptrdiff_t p;
+
+static void fail(const char *message, int status) {
+    fflush(stdout);
+    fprintf(stderr, "%s\n", message);
+    exit(status);
+}
+"#;
+
+    const C_CELL_OVERFLOW: &str = r#"
+static void cell_overflow(int index) {
+    fflush(stdout);
+    fprintf(stderr, "Cell overflow at index %d\n", index);
+    exit(3);
+}
+"#;
+
+    const C_GROW: &str = r#"
+/* grow the tape so that cell `i` fits, at least doubling it, see vm::grow_memory */
+static ptrdiff_t grow(ptrdiff_t i) {
+    size_t left = 0, new_size;
+    cell *new_tape;
+    if (i < 0) {
+        left = (size_t)-i > size ? (size_t)-i : size;
+        new_size = size + left;
+    } else {
+        size_t times = (size_t)i / size + 1;
+        new_size = (times > 2 ? times : 2) * size;
+    }
+    new_tape = calloc(new_size, sizeof(cell));
+    if (new_tape == NULL) fail("Memory error", 2);
+    memcpy(new_tape + left, tape, size * sizeof(cell));
+    free(tape);
+    tape = new_tape;
+    size = new_size;
+    p += left;
+    return i + left;
+}
+"#;
+
+    fn c_cell_type(cell_type: VMCellType) -> &'static str {
+        match cell_type {
+            VMCellType::U8 => "uint8_t",
+            VMCellType::U16 => "uint16_t",
+            VMCellType::U32 => "uint32_t",
+            VMCellType::U64 => "uint64_t",
+        }
+    }
+
+    fn gen_c_normal(
+        irs: &Vec<IRNode>,
+        options: &Options,
+        depth: usize,
+        position: &mut usize,
+        code: &mut String,
+    ) {
+        let indent = "    ".repeat(depth);
+        for ir in irs {
+            let ir_index = *position;
+            *position += 1;
+            let line = match &ir.ir {
+                BFIR::Add(x, o) if options.checked_cells => format!(
+                    "{{ cell *c = at(p + {o}); if ({x}u > CELL_MAX || *c > CELL_MAX - {x}u) cell_overflow({ir_index}); *c += {x}u; }}"
+                ),
+                BFIR::Sub(x, o) if options.checked_cells => format!(
+                    "{{ cell *c = at(p + {o}); if (*c < {x}u) cell_overflow({ir_index}); *c -= {x}u; }}"
+                ),
+                BFIR::Add(x, o) => format!("*at(p + {o}) += (cell){x}u;"),
+                BFIR::Sub(x, o) => format!("*at(p + {o}) -= (cell){x}u;"),
+                BFIR::MoveLeft(x) => format!("p = move(p - {x});"),
+                BFIR::MoveRight(x) => format!("p = move(p + {x});"),
+                BFIR::SetZero(o) => format!("*at(p + {o}) = 0;"),
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => format!(
+                    // narrow cells are promoted to `int`, where the product
+                    // could overflow
+                    "{{ cell v = *at(p + {base}); if (v) *at(p + {}) += (cell)((uintmax_t)v * (uintmax_t)(cell){factor}ll); }}",
+                    base + offset
+                ),
+                BFIR::Scan { stride } => format!("while (tape[p]) p = move(p + {stride});"),
+                BFIR::Input(o) => {
+                    let eof = match options.eof_type {
+                        VMEofType::Error => "fail(\"IO Error\", 1);",
+                        VMEofType::Zero => "*c = 0;",
+                        VMEofType::MinusOne => "*c = (cell)-1;",
+                        VMEofType::Unchanged => "",
+                    };
+                    format!(
+                        "{{ cell *c = at(p + {o}); int ch = getchar(); if (ch != EOF) *c = (cell)ch; else {{ {eof} }} }}"
+                    )
+                }
+                BFIR::Output(o) => format!(
+                    "if (putchar((unsigned char)*at(p + {o})) == EOF) fail(\"IO Error\", 1);"
+                ),
+                BFIR::Loop(x) => {
+                    *code += &format!("{indent}while (tape[p]) {{\n");
+                    gen_c_normal(&x.borrow(), options, depth + 1, position, code);
+                    *code += &format!("{indent}}}\n");
+                    continue;
+                }
+            };
+            *code += &format!("{indent}{line}\n");
+        }
+    }
+
+    /// Translate `irs` into a standalone C program with the tape size, cell
+    /// width, EOF policy and checks of `options`. Like the ELF executables it
+    /// exits with 1 on an IO error, 2 when the pointer leaves the tape and 3
+    /// on a cell overflow.
+    pub fn gen_c(irs: &Vec<IRNode>, options: &Options) -> String {
+        let mut code = String::new();
+        code += "/* generated by bfjit */\n";
+        code += "#include <stddef.h>\n#include <stdint.h>\n#include <stdio.h>\n";
+        code += "#include <stdlib.h>\n#include <string.h>\n\n";
+        code += &format!("typedef {} cell;\n", c_cell_type(options.cell_type));
+        code += &format!(
+            "#define CELL_MAX ((cell)-1)\n#define TAPE_SIZE {}\n\n",
+            options.tape_size
+        );
+        code += C_RUNTIME;
+        if options.checked_cells {
+            code += C_CELL_OVERFLOW;
+        }
+        if options.growable {
+            code += C_GROW;
+        }
+        code += "\n/* index of cell `i`, which must be on the tape */\n";
+        code += "static ptrdiff_t move(ptrdiff_t i) {\n";
+        code += "    if (i >= 0 && (size_t)i < size) return i;\n";
+        if options.growable {
+            code += "    return grow(i);\n";
+        } else {
+            code += "    fail(\"Memory error\", 2);\n    return 0;\n";
+        }
+        code += "}\n\n";
+        code += "static cell *at(ptrdiff_t i) {\n    i = move(i);\n    return &tape[i];\n}\n\n";
+        code += "int main(void) {\n";
+        code += "    size = TAPE_SIZE;\n";
+        code += "    tape = calloc(size, sizeof(cell));\n";
+        code += "    if (tape == NULL) fail(\"Memory error\", 2);\n";
+        gen_c_normal(irs, options, 1, &mut 0, &mut code);
+        code += "    return 0;\n}\n";
+        return code;
+    }
+}
+
+pub mod wasmgen {
+    use crate::bfparser::frontend::ir::{IRNode, BFIR};
+    use crate::bftype::bfcate::bfcate::{VMCellType, VMEofType};
+    use crate::bftype::bferror;
+    use crate::bfvm::bfjit::vm;
+    use crate::Options;
+
+    /// Size of a wasm memory page.
+    pub const WASM_PAGE_SIZE: usize = 0x1_0000;
+    /// Largest tape a 32-bit linear memory can hold.
+    pub const WASM_MEMORY_MAX: usize = 0xFFFF_0000;
+
+    /// Instructions and value type used for the cells of a tape.
+    struct CellOps {
+        ty: &'static str,
+        load: &'static str,
+        store: &'static str,
+        bytes: i64,
+        max: u64,
+    }
+
+    impl CellOps {
+        fn new(cell_type: VMCellType) -> Self {
+            let (ty, load, store) = match cell_type {
+                VMCellType::U8 => ("i32", "i32.load8_u", "i32.store8"),
+                VMCellType::U16 => ("i32", "i32.load16_u", "i32.store16"),
+                VMCellType::U32 => ("i32", "i32.load", "i32.store"),
+                VMCellType::U64 => ("i64", "i64.load", "i64.store"),
+            };
+            let bytes = cell_type.bytes();
+            CellOps {
+                ty,
+                load,
+                store,
+                bytes: bytes as i64,
+                max: u64::MAX >> (64 - 8 * bytes),
+            }
+        }
+
+        /// A constant of the cell type, wrapped to its width.
+        fn constant(&self, value: u64) -> String {
+            if self.ty == "i64" {
+                format!("i64.const {}", value as i64)
+            } else {
+                format!("i32.const {}", value as u32 as i32)
+            }
+        }
+
+        /// Turn the cell on top of the stack into an `i32` condition.
+        fn is_zero(&self) -> String {
+            format!("{}.eqz", self.ty)
+        }
+    }
+
+    struct Wat<'a> {
+        options: &'a Options,
+        cell: CellOps,
+        tape_bytes: usize,
+        code: String,
+        depth: usize,
+        /// Preorder position of the next instruction.
+        next: usize,
+        /// Preorder position of the instruction being translated.
+        at: usize,
+    }
+
+    impl<'a> Wat<'a> {
+        fn line(&mut self, line: &str) {
+            self.code += &"  ".repeat(self.depth);
+            self.code += line;
+            self.code += "\n";
+        }
+
+        /// Record the instruction being translated in `$at`, for the host
+        /// to place an error.
+        fn set_at(&mut self) {
+            self.line(&format!("i32.const {}", self.at));
+            self.line("global.set $at");
+        }
+
+        fn fail(&mut self, status: i32) {
+            self.set_at();
+            self.line(&format!("i32.const {}", status));
+            self.line("return");
+        }
+
+        /// Return `status` if the `i32` on top of the stack is not zero.
+        fn fail_if(&mut self, status: i32) {
+            self.line("if");
+            self.depth += 1;
+            self.fail(status);
+            self.depth -= 1;
+            self.line("end");
+        }
+
+        /// Fail unless the byte address on top of the stack is on the tape,
+        /// with 4 if it is `below` the tape and 2 otherwise. A negative
+        /// address wraps around and fails as well.
+        fn check_bounds(&mut self, below: bool) {
+            self.line(&format!("i32.const {}", self.tape_bytes as u32 as i32));
+            self.line("i32.ge_u");
+            self.fail_if(if below { 4 } else { 2 });
+        }
+
+        /// Leave the address of the cell at `offset` in `$a`.
+        fn address(&mut self, offset: i64) {
+            self.line("local.get $p");
+            if offset != 0 {
+                self.line(&format!("i32.const {}", (offset * self.cell.bytes) as i32));
+                self.line("i32.add");
+                self.line("local.tee $a");
+                self.check_bounds(offset < 0);
+            } else {
+                self.line("local.set $a");
+            }
+        }
+
+        fn move_by(&mut self, cells: i64) {
+            self.line("local.get $p");
+            self.line(&format!("i32.const {}", (cells * self.cell.bytes) as i32));
+            self.line("i32.add");
+            self.line("local.tee $p");
+            self.check_bounds(cells < 0);
+        }
+
+        fn load(&mut self) {
+            self.line("local.get $a");
+            self.line(self.cell.load);
+        }
+
+        fn add(&mut self, value: u64, sub: bool, checked: bool) {
+            let op = if sub { "sub" } else { "add" };
+            self.line("local.get $a");
+            self.load();
+            if checked {
+                self.line("local.tee $v");
+                if sub {
+                    self.line(&self.cell.constant(value));
+                    self.line(&format!("{}.lt_u", self.cell.ty));
+                    self.fail_if(3);
+                } else if value > self.cell.max {
+                    self.line("drop");
+                    self.fail(3);
+                } else {
+                    self.line(&self.cell.constant(self.cell.max - value));
+                    self.line(&format!("{}.gt_u", self.cell.ty));
+                    self.fail_if(3);
+                }
+                self.line("local.get $v");
+            }
+            self.line(&self.cell.constant(value));
+            self.line(&format!("{}.{}", self.cell.ty, op));
+            self.line(self.cell.store);
+        }
+
+        /// `block`/`loop` pair running `body` while the current cell is not zero.
+        fn while_nonzero(&mut self, body: impl FnOnce(&mut Self)) {
+            self.line("block");
+            self.depth += 1;
+            self.line("loop");
+            self.depth += 1;
+            self.line("local.get $p");
+            self.line(self.cell.load);
+            self.line(&self.cell.is_zero());
+            self.line("br_if 1");
+            body(self);
+            self.line("br 0");
+            self.depth -= 1;
+            self.line("end");
+            self.depth -= 1;
+            self.line("end");
+        }
+
+        fn gen_normal(&mut self, irs: &Vec<IRNode>) {
+            for ir in irs {
+                self.at = self.next;
+                self.next += 1;
+                match &ir.ir {
+                    BFIR::Add(x, o) => {
+                        self.address(*o);
+                        self.add(*x as u64, false, self.options.checked_cells);
+                    }
+                    BFIR::Sub(x, o) => {
+                        self.address(*o);
+                        self.add(*x as u64, true, self.options.checked_cells);
+                    }
+                    BFIR::MoveLeft(x) => self.move_by(-(*x as i64)),
+                    BFIR::MoveRight(x) => self.move_by(*x as i64),
+                    BFIR::SetZero(o) => {
+                        self.address(*o);
+                        self.line("local.get $a");
+                        self.line(&self.cell.constant(0));
+                        self.line(self.cell.store);
+                    }
+                    BFIR::MulAdd {
+                        base,
+                        offset,
+                        factor,
+                    } => {
+                        self.address(*base);
+                        self.load();
+                        self.line("local.tee $v");
+                        self.line(&self.cell.is_zero());
+                        self.line("i32.eqz");
+                        self.line("if");
+                        self.depth += 1;
+                        self.address(base + offset);
+                        self.line("local.get $a");
+                        self.load();
+                        self.line("local.get $v");
+                        self.line(&self.cell.constant(*factor as u64));
+                        self.line(&format!("{}.mul", self.cell.ty));
+                        self.line(&format!("{}.add", self.cell.ty));
+                        self.line(self.cell.store);
+                        self.depth -= 1;
+                        self.line("end");
+                    }
+                    BFIR::Scan { stride } => self.while_nonzero(|wat| wat.move_by(*stride)),
+                    BFIR::Input(o) => {
+                        self.address(*o);
+                        self.set_at();
+                        self.line("call $read_byte");
+                        self.line("local.tee $c");
+                        self.line("i32.const 0");
+                        self.line("i32.lt_s");
+                        self.line("if");
+                        self.depth += 1;
+                        match self.options.eof_type {
+                            VMEofType::Error => self.fail(1),
+                            VMEofType::Zero | VMEofType::MinusOne => {
+                                let value = match self.options.eof_type {
+                                    VMEofType::Zero => 0,
+                                    _ => u64::MAX,
+                                };
+                                self.line("local.get $a");
+                                self.line(&self.cell.constant(value));
+                                self.line(self.cell.store);
+                            }
+                            VMEofType::Unchanged => {}
+                        }
+                        self.depth -= 1;
+                        self.line("else");
+                        self.depth += 1;
+                        self.line("local.get $a");
+                        self.line("local.get $c");
+                        if self.cell.ty == "i64" {
+                            self.line("i64.extend_i32_u");
+                        }
+                        self.line(self.cell.store);
+                        self.depth -= 1;
+                        self.line("end");
+                    }
+                    BFIR::Output(o) => {
+                        self.address(*o);
+                        self.load();
+                        if self.cell.ty == "i64" {
+                            self.line("i32.wrap_i64");
+                        }
+                        self.set_at();
+                        self.line("call $write_byte");
+                    }
+                    BFIR::Loop(x) => self.while_nonzero(|wat| wat.gen_normal(&x.borrow())),
+                }
+            }
+        }
+    }
+
+    /// Translate `irs` into a WebAssembly module in text format. The module
+    /// imports `env.read_byte: () -> i32`, returning -1 at the end of the
+    /// input, and `env.write_byte: (i32) -> ()`, exports its tape as `memory`
+    /// and the program as `run: () -> i32`. Like the ELF executables `run`
+    /// returns 1 on an IO error, 2 when the pointer leaves the tape on the
+    /// right and 3 on a cell overflow, but 4 when it leaves on the left. It
+    /// then leaves the preorder position of the failing instruction in the
+    /// exported global `at`, which also holds the one of the last `,` or `.`
+    /// while the imports run.
+    pub fn gen_wat(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<String, bferror::error::RuntimeError> {
+        if options.growable {
+            // a linear memory only grows to the right
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Unsupported(
+                    "growable tape is not supported by the wasm backend",
+                ),
+                span: None,
+            });
+        }
+        let tape_bytes = vm::tape_bytes(options)?;
+        if tape_bytes == 0 || tape_bytes > WASM_MEMORY_MAX {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        let cell = CellOps::new(options.cell_type);
+        let mut wat = Wat {
+            options,
+            tape_bytes,
+            code: String::new(),
+            depth: 0,
+            next: 0,
+            at: 0,
+            cell,
+        };
+        wat.line("(module");
+        wat.depth += 1;
+        wat.line("(import \"env\" \"read_byte\" (func $read_byte (result i32)))");
+        wat.line("(import \"env\" \"write_byte\" (func $write_byte (param i32)))");
+        let pages = tape_bytes.div_ceil(WASM_PAGE_SIZE);
+        wat.line(&format!("(memory (export \"memory\") {} {})", pages, pages));
+        wat.line("(global $at (export \"at\") (mut i32) (i32.const 0))");
+        let locals = format!(
+            "(local $p i32) (local $a i32) (local $c i32) (local $v {})",
+            wat.cell.ty
+        );
+        wat.line(&format!("(func (export \"run\") (result i32) {}", locals));
+        wat.depth += 1;
+        wat.gen_normal(irs);
+        wat.line("i32.const 0");
+        wat.depth -= 1;
+        wat.line(")");
+        wat.depth -= 1;
+        wat.line(")");
+        return Ok(wat.code);
+    }
+
+    /// Translate `irs` into a binary WebAssembly module, see `gen_wat`.
+    pub fn gen_wasm(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<Vec<u8>, bferror::error::RuntimeError> {
+        let text = gen_wat(irs, options)?;
+        return wat::parse_str(text).map_err(|_| bferror::error::RuntimeError {
+            index: 1,
+            kind: bferror::error::RuntimeErrorKind::Unknown,
+            span: None,
+        });
+    }
+}
+
+pub mod llvmgen {
+    use crate::bfparser::frontend::ir::{IRNode, BFIR};
+    use crate::bftype::bfcate::bfcate::{VMCellType, VMEofType};
+    use crate::bftype::bferror;
+    use crate::bfvm::bfjit::vm;
+    use crate::Options;
+
+    // the errors are the exit status of the program cast to a pointer, see
+    // `cgen::C_RUNTIME`
+    const LL_RUNTIME: &str = r#"
+@io_error = private constant [10 x i8] c"IO Error\0A\00"
+@memory_error = private constant [14 x i8] c"Memory error\0A\00"
+@cell_overflow = private constant [28 x i8] c"Cell overflow at index %ld\0A\00"
+
+declare i32 @getchar()
+declare i32 @putchar(i32)
+declare i32 @fflush(ptr)
+declare i32 @dprintf(i32, ptr, ...)
+declare ptr @calloc(i64, i64)
+
+define private ptr @bf_fail(ptr %message, i64 %status) {
+  call i32 @fflush(ptr null)
+  call i32 (i32, ptr, ...) @dprintf(i32 2, ptr %message)
+  %error = inttoptr i64 %status to ptr
+  ret ptr %error
+}
+
+define ptr @bf_overflow_error() {
+  %error = call ptr @bf_fail(ptr @memory_error, i64 2)
+  ret ptr %error
+}
+
+define ptr @bf_cell_overflow(i64 %index) {
+  call i32 @fflush(ptr null)
+  call i32 (i32, ptr, ...) @dprintf(i32 2, ptr @cell_overflow, i64 %index)
+  ret ptr inttoptr (i64 3 to ptr)
+}
+"#;
+
+    struct Ll {
+        cell: &'static str,
+        cell_bytes: i64,
+        checked_cells: bool,
+        code: String,
+        next: usize,
+        position: usize,
+    }
+
+    impl Ll {
+        fn line(&mut self, line: &str) {
+            self.code += "  ";
+            self.code += line;
+            self.code += "\n";
+        }
+
+        fn label(&mut self, label: &str) {
+            self.code += label;
+            self.code += ":\n";
+        }
+
+        /// A fresh name for a value or a block.
+        fn fresh(&mut self, prefix: &str) -> String {
+            self.next += 1;
+            format!("{}{}", prefix, self.next)
+        }
+
+        /// A constant of the cell type, wrapped to its width.
+        fn constant(&self, value: u64) -> i64 {
+            let shift = 64 - 8 * self.cell_bytes;
+            return ((value << shift) as i64) >> shift;
+        }
+
+        /// Branch to `target` if `cond` holds, going on in a new block otherwise.
+        fn branch_if(&mut self, cond: &str, target: &str) {
+            let next = self.fresh("L");
+            self.line(&format!(
+                "br i1 {}, label %{}, label %{}",
+                cond, target, next
+            ));
+            self.label(&next);
+        }
+
+        /// Leave the JIT on a non-null error returned by a callback.
+        fn check_error(&mut self, err: &str) {
+            let failed = self.fresh("%t");
+            self.line(&format!("{} = icmp ne ptr {}, null", failed, err));
+            let fail = self.fresh("E");
+            let next = self.fresh("L");
+            self.line(&format!(
+                "br i1 {}, label %{}, label %{}",
+                failed, fail, next
+            ));
+            self.label(&fail);
+            self.line(&format!("ret ptr {}", err));
+            self.label(&next);
+        }
+
+        /// Byte offset of the cell at `offset`, checked against the memory.
+        fn offset(&mut self, offset: i64) -> String {
+            let p = self.fresh("%t");
+            self.line(&format!("{} = load i64, ptr %p", p));
+            if offset == 0 {
+                return p;
+            }
+            let at = self.fresh("%t");
+            self.line(&format!(
+                "{} = add i64 {}, {}",
+                at,
+                p,
+                offset * self.cell_bytes
+            ));
+            self.check_bounds(&at);
+            return at;
+        }
+
+        fn check_bounds(&mut self, at: &str) {
+            let outside = self.fresh("%t");
+            self.line(&format!("{} = icmp uge i64 {}, %size", outside, at));
+            self.branch_if(&outside, "overflow");
+        }
+
+        /// Address of the cell at `offset`.
+        fn address(&mut self, offset: i64) -> String {
+            let at = self.offset(offset);
+            let ptr = self.fresh("%t");
+            self.line(&format!(
+                "{} = getelementptr i8, ptr %memory_start, i64 {}",
+                ptr, at
+            ));
+            return ptr;
+        }
+
+        fn load(&mut self, ptr: &str) -> String {
+            let value = self.fresh("%t");
+            self.line(&format!("{} = load {}, ptr {}", value, self.cell, ptr));
+            return value;
+        }
+
+        fn move_by(&mut self, cells: i64) {
+            let p = self.fresh("%t");
+            self.line(&format!("{} = load i64, ptr %p", p));
+            let moved = self.fresh("%t");
+            self.line(&format!(
+                "{} = add i64 {}, {}",
+                moved,
+                p,
+                cells * self.cell_bytes
+            ));
+            self.check_bounds(&moved);
+            self.line(&format!("store i64 {}, ptr %p", moved));
+        }
+
+        fn add(&mut self, value: u64, offset: i64, sub: bool, index: usize) {
+            let ptr = self.address(offset);
+            let old = self.load(&ptr);
+            if self.checked_cells {
+                let max = u64::MAX >> (64 - 8 * self.cell_bytes);
+                let overflow = self.fresh("C");
+                if !sub && value > max {
+                    self.line(&format!("br label %{}", overflow));
+                } else {
+                    let cond = self.fresh("%t");
+                    if sub {
+                        let value = self.constant(value);
+                        self.line(&format!(
+                            "{} = icmp ult {} {}, {}",
+                            cond, self.cell, old, value
+                        ));
+                    } else {
+                        let limit = self.constant(max - value);
+                        self.line(&format!(
+                            "{} = icmp ugt {} {}, {}",
+                            cond, self.cell, old, limit
+                        ));
+                    }
+                    let next = self.fresh("L");
+                    self.line(&format!(
+                        "br i1 {}, label %{}, label %{}",
+                        cond, overflow, next
+                    ));
+                    self.label(&next);
+                }
+                // the overflow block is placed after the store, out of the way
+                let new = self.fresh("%t");
+                let op = if sub { "sub" } else { "add" };
+                let value = self.constant(value);
+                self.line(&format!(
+                    "{} = {} {} {}, {}",
+                    new, op, self.cell, old, value
+                ));
+                self.line(&format!("store {} {}, ptr {}", self.cell, new, ptr));
+                let next = self.fresh("L");
+                self.line(&format!("br label %{}", next));
+                self.label(&overflow);
+                let err = self.fresh("%t");
+                self.line(&format!(
+                    "{} = call ptr @bf_cell_overflow(i64 {})",
+                    err, index
+                ));
+                self.line(&format!("ret ptr {}", err));
+                self.label(&next);
+                return;
+            }
+            let new = self.fresh("%t");
+            let op = if sub { "sub" } else { "add" };
+            let value = self.constant(value);
+            self.line(&format!(
+                "{} = {} {} {}, {}",
+                new, op, self.cell, old, value
+            ));
+            self.line(&format!("store {} {}, ptr {}", self.cell, new, ptr));
+        }
+
+        /// Run `body` while the current cell is not zero.
+        fn while_nonzero(&mut self, body: impl FnOnce(&mut Self)) {
+            let head = self.fresh("H");
+            let enter = self.fresh("B");
+            let exit = self.fresh("X");
+            self.line(&format!("br label %{}", head));
+            self.label(&head);
+            let ptr = self.address(0);
+            let value = self.load(&ptr);
+            let zero = self.fresh("%t");
+            self.line(&format!("{} = icmp eq {} {}, 0", zero, self.cell, value));
+            self.line(&format!(
+                "br i1 {}, label %{}, label %{}",
+                zero, exit, enter
+            ));
+            self.label(&enter);
+            body(self);
+            self.line(&format!("br label %{}", head));
+            self.label(&exit);
+        }
+
+        fn gen_normal(&mut self, irs: &Vec<IRNode>) {
+            for ir in irs {
+                let index = self.position;
+                self.position += 1;
+                match &ir.ir {
+                    BFIR::Add(x, o) => self.add(*x as u64, *o, false, index),
+                    BFIR::Sub(x, o) => self.add(*x as u64, *o, true, index),
+                    BFIR::MoveLeft(x) => self.move_by(-(*x as i64)),
+                    BFIR::MoveRight(x) => self.move_by(*x as i64),
+                    BFIR::SetZero(o) => {
+                        let ptr = self.address(*o);
+                        self.line(&format!("store {} 0, ptr {}", self.cell, ptr));
+                    }
+                    BFIR::MulAdd {
+                        base,
+                        offset,
+                        factor,
+                    } => {
+                        let ptr = self.address(*base);
+                        let value = self.load(&ptr);
+                        let zero = self.fresh("%t");
+                        self.line(&format!("{} = icmp eq {} {}, 0", zero, self.cell, value));
+                        let then = self.fresh("L");
+                        let next = self.fresh("L");
+                        self.line(&format!("br i1 {}, label %{}, label %{}", zero, next, then));
+                        self.label(&then);
+                        let target = self.address(base + offset);
+                        let old = self.load(&target);
+                        let product = self.fresh("%t");
+                        let factor = self.constant(*factor as u64);
+                        self.line(&format!(
+                            "{} = mul {} {}, {}",
+                            product, self.cell, value, factor
+                        ));
+                        let new = self.fresh("%t");
+                        self.line(&format!("{} = add {} {}, {}", new, self.cell, old, product));
+                        self.line(&format!("store {} {}, ptr {}", self.cell, new, target));
+                        self.line(&format!("br label %{}", next));
+                        self.label(&next);
+                    }
+                    BFIR::Scan { stride } => self.while_nonzero(|ll| ll.move_by(*stride)),
+                    BFIR::Input(o) | BFIR::Output(o) => {
+                        let callback = match &ir.ir {
+                            BFIR::Input(_) => "bf_put_byte",
+                            _ => "bf_get_byte",
+                        };
+                        let ptr = self.address(*o);
+                        let err = self.fresh("%t");
+                        self.line(&format!(
+                            "{} = call ptr @{}(ptr %this, ptr {})",
+                            err, callback, ptr
+                        ));
+                        self.check_error(&err);
+                    }
+                    BFIR::Loop(x) => self.while_nonzero(|ll| ll.gen_normal(&x.borrow())),
+                }
+            }
+        }
+    }
+
+    fn ll_cell_type(cell_type: VMCellType) -> &'static str {
+        match cell_type {
+            VMCellType::U8 => "i8",
+            VMCellType::U16 => "i16",
+            VMCellType::U32 => "i32",
+            VMCellType::U64 => "i64",
+        }
+    }
+
+    /// `@bf_put_byte` and `@bf_get_byte` on the C library, for `,` and `.`.
+    fn gen_io(cell: &str, eof_type: VMEofType) -> String {
+        let (read, write) = match cell {
+            "i32" => ("bitcast", "bitcast"),
+            "i64" => ("zext", "trunc"),
+            _ => ("trunc", "zext"),
+        };
+        let eof = match eof_type {
+            VMEofType::Error => {
+                "  %error = call ptr @bf_fail(ptr @io_error, i64 1)\n  ret ptr %error\n".to_string()
+            }
+            VMEofType::Zero => format!("  store {} 0, ptr %cell\n  ret ptr null\n", cell),
+            VMEofType::MinusOne => format!("  store {} -1, ptr %cell\n  ret ptr null\n", cell),
+            VMEofType::Unchanged => "  ret ptr null\n".to_string(),
+        };
+        let mut code = String::new();
+        code += "\ndefine ptr @bf_put_byte(ptr %this, ptr %cell) {\n";
+        code += "  %c = call i32 @getchar()\n";
+        code += "  %end = icmp eq i32 %c, -1\n";
+        code += "  br i1 %end, label %eof, label %read\n";
+        code += "read:\n";
+        code += &format!("  %value = {} i32 %c to {}\n", read, cell);
+        code += &format!("  store {} %value, ptr %cell\n", cell);
+        code += "  ret ptr null\n";
+        code += "eof:\n";
+        code += &eof;
+        code += "}\n";
+        code += "\ndefine ptr @bf_get_byte(ptr %this, ptr %cell) {\n";
+        code += &format!("  %value = load {}, ptr %cell\n", cell);
+        code += &format!("  %c = {} {} %value to i32\n", write, cell);
+        code += "  %byte = and i32 %c, 255\n";
+        code += "  %written = call i32 @putchar(i32 %byte)\n";
+        code += "  %failed = icmp eq i32 %written, -1\n";
+        code += "  br i1 %failed, label %fail, label %done\n";
+        code += "fail:\n";
+        code += "  %error = call ptr @bf_fail(ptr @io_error, i64 1)\n";
+        code += "  ret ptr %error\n";
+        code += "done:\n";
+        code += "  ret ptr null\n";
+        code += "}\n";
+        return code;
+    }
+
+    /// `@main` running `@bf_main` on a zeroed tape, exiting with its error.
+    fn gen_main(options: &Options) -> Result<String, bferror::error::RuntimeError> {
+        // the size of the tape is an `i64` constant
+        let tape_bytes = match vm::tape_bytes(options) {
+            Ok(tape_bytes) if tape_bytes <= i64::MAX as usize => tape_bytes,
+            _ => {
+                return Err(bferror::error::RuntimeError {
+                    index: 1,
+                    kind: bferror::error::RuntimeErrorKind::Unsupported(
+                        "tape is too large for the llvm output",
+                    ),
+                    span: None,
+                })
+            }
+        };
+        let bytes = options.cell_type.bytes();
+        let mut code = String::new();
+        code += "\ndefine i32 @main() {\n";
+        code += &format!(
+            "  %tape = call ptr @calloc(i64 {}, i64 {})\n",
+            options.tape_size, bytes
+        );
+        code += "  %none = icmp eq ptr %tape, null\n";
+        code += "  br i1 %none, label %fail, label %run\n";
+        code += "fail:\n";
+        code += "  call ptr @bf_overflow_error()\n";
+        code += "  ret i32 2\n";
+        code += "run:\n";
+        code += &format!("  %end = getelementptr i8, ptr %tape, i64 {}\n", tape_bytes);
+        code += "  %error = call ptr @bf_main(ptr null, ptr %tape, ptr %end)\n";
+        code += "  %status = ptrtoint ptr %error to i32\n";
+        code += "  ret i32 %status\n";
+        code += "}\n";
+        return Ok(code);
+    }
+
+    /// Translate `irs` into a standalone module of textual LLVM IR, run with
+    /// `lli` or built with `clang`. It uses opaque pointers, so it needs
+    /// LLVM 15 or later, or LLVM 14 with `-opaque-pointers`. Like the C
+    /// program it exits with 1 on an IO error, 2 when the pointer leaves the
+    /// tape and 3 on a cell overflow.
+    ///
+    /// `@bf_main` has the signature of the JIT code and calls back into the
+    /// runtime like it does, each function returning an error or null:
+    ///
+    /// - `ptr @bf_main(ptr this, ptr memory_start, ptr memory_end)`
+    /// - `ptr @bf_put_byte(ptr this, ptr cell)` for `,`
+    /// - `ptr @bf_get_byte(ptr this, ptr cell)` for `.`
+    /// - `ptr @bf_overflow_error()` when the pointer leaves the memory
+    /// - `ptr @bf_cell_overflow(i64 index)` on a cell overflow in checked mode
+    pub fn gen_llvm(
+        irs: &Vec<IRNode>,
+        options: &Options,
+    ) -> Result<String, bferror::error::RuntimeError> {
+        if options.growable {
+            // growing reloads the memory from the layout of `VMStruct`
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Unsupported(
+                    "growable tape is not supported by the llvm output",
+                ),
+                span: None,
+            });
+        }
+        let mut ll = Ll {
+            cell: ll_cell_type(options.cell_type),
+            cell_bytes: options.cell_type.bytes() as i64,
+            checked_cells: options.checked_cells,
+            code: String::new(),
+            next: 0,
+            position: 0,
+        };
+        ll.code += "; generated by bfjit\n";
+        ll.code += LL_RUNTIME;
+        ll.code += &gen_io(ll.cell, options.eof_type);
+        ll.code += &gen_main(options)?;
+        ll.code += "\ndefine ptr @bf_main(ptr %this, ptr %memory_start, ptr %memory_end) {\n";
+        ll.label("entry");
+        ll.line("%p = alloca i64");
+        ll.line("store i64 0, ptr %p");
+        ll.line("%start = ptrtoint ptr %memory_start to i64");
+        ll.line("%end = ptrtoint ptr %memory_end to i64");
+        ll.line("%size = sub i64 %end, %start");
+        ll.gen_normal(irs);
+        ll.line("ret ptr null");
+        ll.label("overflow");
+        ll.line("%overflow_error = call ptr @bf_overflow_error()");
+        ll.line("ret ptr %overflow_error");
+        ll.code += "}\n";
+        return Ok(ll.code);
+    }
+}
+
+pub mod disasm {
+    use capstone::prelude::*;
+    use std::iter::Peekable;
+    use std::slice::Iter;
+
+    use crate::bfparser::frontend::ir;
+    use crate::bfparser::frontend::ir::IRNode;
+    use crate::bftype::bfcate::bfcate::VMArchType;
+
+    /// Print the IR instructions whose code starts at or before `offset`.
+    fn print_marks(
+        result: &mut String,
+        marks: &mut Peekable<Iter<(usize, usize)>>,
+        offset: usize,
+        names: &[String],
+        seen: &mut [bool],
+    ) {
+        while let Some(&(_, position)) = marks.next_if(|(at, _)| *at <= offset) {
+            if seen[position] {
+                *result += &format!("; {}: end {}\n", position, names[position]);
+            } else {
+                seen[position] = true;
+                *result += &format!("; {}: {}\n", position, names[position]);
+            }
+        }
+    }
+
+    /// The disassembler of `vm_arch_type`, printing Intel syntax on x86.
+    fn capstone(vm_arch_type: &VMArchType) -> CsResult<Capstone> {
+        match vm_arch_type {
+            VMArchType::X64 => Capstone::new()
+                .x86()
+                .mode(arch::x86::ArchMode::Mode64)
+                .syntax(arch::x86::ArchSyntax::Intel)
+                .build(),
+            VMArchType::X32 => Capstone::new()
+                .x86()
+                .mode(arch::x86::ArchMode::Mode32)
+                .syntax(arch::x86::ArchSyntax::Intel)
+                .build(),
+            VMArchType::AArch64 => Capstone::new()
+                .arm64()
+                .mode(arch::arm64::ArchMode::Arm)
+                .build(),
+        }
+    }
+
+    /// Disassemble the `vm_arch_type` code loaded at `ip`, printing the
+    /// instruction starting at each of the `(code offset, position)` marks
+    /// of a `CodeMap`. Bytes which aren't instructions are printed as
+    /// `.inst` words on aarch64 and `.byte` directives on x86.
+    pub fn disassemble(
+        code: &[u8],
+        vm_arch_type: &VMArchType,
+        ip: u64,
+        marks: &[(usize, usize)],
+        irs: &Vec<IRNode>,
+    ) -> String {
+        let names = ir::preorder(irs);
+        let mut seen = vec![false; names.len()];
+        let mut marks = marks.iter().peekable();
+        let cs = capstone(vm_arch_type);
+        let mut result = String::new();
+        let mut offset = 0;
+        while offset < code.len() {
+            print_marks(&mut result, &mut marks, offset, &names, &mut seen);
+            let address = ip + offset as u64;
+            let decoded = cs.as_ref().ok().and_then(|cs| {
+                let instructions = cs.disasm_count(&code[offset..], address, 1).ok()?;
+                let instruction = instructions.iter().next()?;
+                let text = format!(
+                    "{} {}",
+                    instruction.mnemonic().unwrap_or(""),
+                    instruction.op_str().unwrap_or("")
+                );
+                Some((instruction.bytes().len(), text.trim_end().to_string()))
+            });
+            let (len, text) = match (decoded, code.get(offset..offset + 4)) {
+                (Some(decoded), _) => decoded,
+                (None, Some(word)) if *vm_arch_type == VMArchType::AArch64 => {
+                    let word = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+                    (4, format!(".inst 0x{:08x}", word))
+                }
+                (None, _) => (1, format!(".byte 0x{:02x}", code[offset])),
+            };
+            let bytes: Vec<String> = code[offset..offset + len]
+                .iter()
+                .map(|x| format!("{:02x}", x))
+                .collect();
+            result += &format!("{:08x}  {:<30} {}\n", address, bytes.join(" "), text);
+            offset += len;
+        }
+        return result;
+    }
+}
+
+pub mod elf {
+    /// Load address of the code segment.
+    pub const ELF32_BASE_ADDR: u32 = 0x0804_8000;
+    /// Load address of the zero-initialised memory segment.
+    pub const ELF32_MEMORY_ADDR: u32 = 0x1000_0000;
+    /// Largest memory segment that fits below the 3GiB user space limit.
+    pub const ELF32_MEMORY_MAX: usize = 0xB000_0000;
+
+    /// Load address of the 64-bit code segment.
+    pub const ELF64_BASE_ADDR: u64 = 0x40_0000;
+    /// Load address of the 64-bit zero-initialised memory segment.
+    pub const ELF64_MEMORY_ADDR: u64 = 0x1_0000_0000;
+    /// Largest 64-bit memory segment, well below the 47-bit user space limit.
+    pub const ELF64_MEMORY_MAX: usize = 1 << 44;
+
+    /// `e_machine` of x86_64 executables.
+    pub const EM_X86_64: u16 = 62;
+    /// `e_machine` of aarch64 executables.
+    pub const EM_AARCH64: u16 = 183;
+
+    const ELF32_EHDR_SIZE: u32 = 52;
+    const ELF32_PHDR_SIZE: u32 = 32;
+    /// Address of the first code byte, following the headers.
+    pub const ELF32_ENTRY_ADDR: u32 = ELF32_BASE_ADDR + ELF32_EHDR_SIZE + 2 * ELF32_PHDR_SIZE;
+
+    const ELF64_EHDR_SIZE: u64 = 64;
+    const ELF64_PHDR_SIZE: u64 = 56;
+    /// Address of the first 64-bit code byte, following the headers.
+    pub const ELF64_ENTRY_ADDR: u64 = ELF64_BASE_ADDR + ELF64_EHDR_SIZE + 2 * ELF64_PHDR_SIZE;
+
+    fn push_u16(buf: &mut Vec<u8>, x: u16) {
+        buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    fn push_u32(buf: &mut Vec<u8>, x: u32) {
+        buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    fn push_u64(buf: &mut Vec<u8>, x: u64) {
+        buf.extend_from_slice(&x.to_le_bytes());
+    }
+
+    fn push_phdr32(buf: &mut Vec<u8>, offset: u32, addr: u32, filesz: u32, memsz: u32, flags: u32) {
+        push_u32(buf, 1); // PT_LOAD
+        push_u32(buf, offset);
+        push_u32(buf, addr); // p_vaddr
+        push_u32(buf, addr); // p_paddr
+        push_u32(buf, filesz);
+        push_u32(buf, memsz);
+        push_u32(buf, flags);
+        push_u32(buf, 0x1000); // p_align
+    }
+
+    /// Wrap `code` into an i386 ELF executable entered at its first byte,
+    /// with `memory_size` zeroed bytes mapped at `ELF32_MEMORY_ADDR`.
+    pub fn write_elf32(code: &[u8], memory_size: usize) -> Vec<u8> {
+        let header_size = ELF32_EHDR_SIZE + 2 * ELF32_PHDR_SIZE;
+        let file_size = header_size + code.len() as u32;
+        let mut buf = vec![];
+        buf.extend_from_slice(b"\x7fELF");
+        buf.extend_from_slice(&[1, 1, 1, 0]); // 32-bit, little endian, version 1, SysV
+        buf.extend_from_slice(&[0; 8]);
+        push_u16(&mut buf, 2); // ET_EXEC
+        push_u16(&mut buf, 3); // EM_386
+        push_u32(&mut buf, 1); // EV_CURRENT
+        push_u32(&mut buf, ELF32_ENTRY_ADDR); // e_entry
+        push_u32(&mut buf, ELF32_EHDR_SIZE); // e_phoff
+        push_u32(&mut buf, 0); // e_shoff
+        push_u32(&mut buf, 0); // e_flags
+        push_u16(&mut buf, ELF32_EHDR_SIZE as u16);
+        push_u16(&mut buf, ELF32_PHDR_SIZE as u16);
+        push_u16(&mut buf, 2); // e_phnum
+        push_u16(&mut buf, 0); // e_shentsize
+        push_u16(&mut buf, 0); // e_shnum
+        push_u16(&mut buf, 0); // e_shstrndx
+        push_phdr32(&mut buf, 0, ELF32_BASE_ADDR, file_size, file_size, 5); // R+X
+        push_phdr32(&mut buf, 0, ELF32_MEMORY_ADDR, 0, memory_size as u32, 6); // R+W
+        buf.extend_from_slice(code);
+        return buf;
+    }
+
+    fn push_phdr64(buf: &mut Vec<u8>, offset: u64, addr: u64, filesz: u64, memsz: u64, flags: u32) {
+        push_u32(buf, 1); // PT_LOAD
+        push_u32(buf, flags);
+        push_u64(buf, offset);
+        push_u64(buf, addr); // p_vaddr
+        push_u64(buf, addr); // p_paddr
+        push_u64(buf, filesz);
+        push_u64(buf, memsz);
+        push_u64(buf, 0x1000); // p_align
+    }
+
+    /// Wrap `code` into a 64-bit ELF executable for `machine`, entered at its
+    /// first byte, with `memory_size` zeroed bytes mapped at `ELF64_MEMORY_ADDR`.
+    pub fn write_elf64(code: &[u8], memory_size: usize, machine: u16) -> Vec<u8> {
+        let header_size = ELF64_EHDR_SIZE + 2 * ELF64_PHDR_SIZE;
+        let file_size = header_size + code.len() as u64;
+        let mut buf = vec![];
+        buf.extend_from_slice(b"\x7fELF");
+        buf.extend_from_slice(&[2, 1, 1, 0]); // 64-bit, little endian, version 1, SysV
+        buf.extend_from_slice(&[0; 8]);
+        push_u16(&mut buf, 2); // ET_EXEC
+        push_u16(&mut buf, machine);
+        push_u32(&mut buf, 1); // EV_CURRENT
+        push_u64(&mut buf, ELF64_ENTRY_ADDR); // e_entry
+        push_u64(&mut buf, ELF64_EHDR_SIZE); // e_phoff
+        push_u64(&mut buf, 0); // e_shoff
+        push_u32(&mut buf, 0); // e_flags
+        push_u16(&mut buf, ELF64_EHDR_SIZE as u16);
+        push_u16(&mut buf, ELF64_PHDR_SIZE as u16);
+        push_u16(&mut buf, 2); // e_phnum
+        push_u16(&mut buf, 0); // e_shentsize
+        push_u16(&mut buf, 0); // e_shnum
+        push_u16(&mut buf, 0); // e_shstrndx
+        push_phdr64(&mut buf, 0, ELF64_BASE_ADDR, file_size, file_size, 5); // R+X
+        push_phdr64(&mut buf, 0, ELF64_MEMORY_ADDR, 0, memory_size as u64, 6); // R+W
+        buf.extend_from_slice(code);
+        return buf;
     }
 }
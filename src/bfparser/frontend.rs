@@ -1,5 +1,6 @@
 pub mod parser {
     use crate::bftype::bferror;
+    use std::fmt;
     #[derive(Debug, Clone, Copy, PartialEq, Eq)]
     pub enum TOKEN {
         Increment, // +
@@ -12,87 +13,239 @@ pub mod parser {
         RightLoop, // ]
     }
 
-    pub fn parse(str: &str) -> Result<Vec<TOKEN>, bferror::error::CompileError> {
+    impl TOKEN {
+        /// The source character of the token.
+        pub fn symbol(&self) -> char {
+            match self {
+                TOKEN::Increment => '+',
+                TOKEN::Decrement => '-',
+                TOKEN::MoveLeft => '<',
+                TOKEN::MoveRight => '>',
+                TOKEN::Input => ',',
+                TOKEN::Output => '.',
+                TOKEN::LeftLoop => '[',
+                TOKEN::RightLoop => ']',
+            }
+        }
+    }
+
+    /// A range of the source, from `line`:`col` to `end_line`:`end_col`
+    /// included, all counted from 1.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct Span {
+        pub line: u32,
+        pub col: u32,
+        pub end_line: u32,
+        pub end_col: u32,
+    }
+
+    impl Span {
+        /// The span of the single character at `line`:`col`.
+        pub fn at(line: u32, col: u32) -> Self {
+            Span {
+                line,
+                col,
+                end_line: line,
+                end_col: col,
+            }
+        }
+
+        /// The span from the start of `self` to the end of `other`.
+        pub fn to(self, other: Span) -> Self {
+            Span {
+                end_line: other.end_line,
+                end_col: other.end_col,
+                ..self
+            }
+        }
+    }
+
+    impl fmt::Display for Span {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "line {}:{}", self.line, self.col)
+        }
+    }
+
+    /// A token and the character it was read from.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Token {
+        pub kind: TOKEN,
+        pub span: Span,
+    }
+
+    /// One token per line, with its index in the stream and its place in
+    /// the source.
+    pub fn dump(tokens: &Vec<Token>) -> String {
+        let mut result = String::new();
+        for (index, token) in tokens.iter().enumerate() {
+            let place = format!("{}:{}", token.span.line, token.span.col);
+            result += &format!(
+                "{:>6}  {:<8}  {}  {:?}\n",
+                index,
+                place,
+                token.kind.symbol(),
+                token.kind
+            );
+        }
+        return result;
+    }
+
+    /// Tokenize `str`, ignoring everything but the eight commands. Every
+    /// unmatched bracket is reported, the first one carrying the others.
+    pub fn parse(str: &str) -> Result<Vec<Token>, bferror::error::CompileError> {
         let mut tokens = vec![];
-        let mut stack: Vec<(u32, u32, u32)> = vec![];
+        let mut stack: Vec<(u32, u32)> = vec![];
+        let mut errors = vec![];
         let mut line = 1;
         let mut col = 0;
         for c in str.chars() {
-            match c {
-                '+' => tokens.push(TOKEN::Increment),
-                '-' => tokens.push(TOKEN::Decrement),
-                '<' => tokens.push(TOKEN::MoveLeft),
-                '>' => tokens.push(TOKEN::MoveRight),
-                ',' => tokens.push(TOKEN::Input),
-                '.' => tokens.push(TOKEN::Output),
+            col += 1;
+            let kind = match c {
+                '+' => TOKEN::Increment,
+                '-' => TOKEN::Decrement,
+                '<' => TOKEN::MoveLeft,
+                '>' => TOKEN::MoveRight,
+                ',' => TOKEN::Input,
+                '.' => TOKEN::Output,
                 '[' => {
-                    stack.push((line, col, tokens.len() as u32));
-                    tokens.push(TOKEN::LeftLoop);
+                    stack.push((line, col));
+                    TOKEN::LeftLoop
                 }
                 ']' => {
-                    stack.pop().ok_or(bferror::error::CompileError {
-                        line,
-                        col,
-                        kind: bferror::error::CompileErrorKind::UnexpectedRightBracket,
-                    })?;
-                    tokens.push(TOKEN::RightLoop);
+                    if stack.pop().is_none() {
+                        errors.push(bferror::error::CompileError {
+                            line,
+                            col,
+                            kind: bferror::error::CompileErrorKind::UnexpectedRightBracket,
+                            others: vec![],
+                        });
+                    }
+                    TOKEN::RightLoop
                 }
                 '\n' => {
                     line += 1;
                     col = 0;
+                    continue;
                 }
-                _ => (),
-            }
-            col += 1;
+                _ => continue,
+            };
+            tokens.push(Token {
+                kind,
+                span: Span::at(line, col),
+            });
         }
-        if let Some((line, col, _)) = stack.pop() {
-            return Err(bferror::error::CompileError {
+        for (line, col) in stack {
+            errors.push(bferror::error::CompileError {
                 line,
                 col,
                 kind: bferror::error::CompileErrorKind::UnclosedLeftBracket,
+                others: vec![],
             });
         }
-        return Ok(tokens);
+        if errors.is_empty() {
+            return Ok(tokens);
+        }
+        errors.sort_by_key(|error| (error.line, error.col));
+        let mut first = errors.remove(0);
+        first.others = errors;
+        return Err(first);
     }
 }
 
 pub mod ir {
-    use crate::bfparser::frontend::parser::TOKEN;
+    use crate::bfparser::frontend::parser::{Span, Token, TOKEN};
     use crate::bftype::bferror;
     use std::cell::Ref;
     use std::cell::RefCell;
+    use std::fmt;
     use std::rc::Rc;
 
+    /// Cell operations carry an offset from the pointer, 0 unless the
+    /// moves around them were folded by `optimizer::reduce_offset`.
     #[derive(Debug, Clone, PartialEq, Eq)]
     pub enum BFIR {
-        Add(u8),                  // + (u8)
-        Sub(u8),                  // - (u8)
-        MoveLeft(u32),            // < (u32)
-        MoveRight(u32),           // > (u32)
-        Input,                    // ,
-        Output,                   // .
-        Loop(RefCell<Vec<BFIR>>), // [ (Vec<BFIR>)]
+        Add(u32, i64),              // + (u32, offset)
+        Sub(u32, i64),              // - (u32, offset)
+        MoveLeft(u32),              // < (u32)
+        MoveRight(u32),             // > (u32)
+        Input(i64),                 // , (offset)
+        Output(i64),                // . (offset)
+        Loop(RefCell<Vec<IRNode>>), // [ (Vec<IRNode>)]
+        SetZero(i64),               // [-] (offset)
+        Scan { stride: i64 },       // [>] / [<] (signed stride)
+        // ptr[base + offset] += ptr[base] * factor
+        MulAdd { base: i64, offset: i64, factor: i64 },
+    }
+
+    /// An instruction and the part of the source it was lowered from. The
+    /// span of a loop covers its brackets, and instructions merged by the
+    /// optimizer cover all of their sources.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct IRNode {
+        pub ir: BFIR,
+        pub span: Span,
+        /// The moves folded into the offset of the instruction, if any.
+        pub moves: Option<Span>,
+    }
+
+    impl IRNode {
+        pub fn new(ir: BFIR, span: Span) -> Self {
+            IRNode {
+                ir,
+                span,
+                moves: None,
+            }
+        }
+
+        pub fn sources(&self) -> Sources {
+            Sources {
+                span: self.span,
+                moves: self.moves,
+            }
+        }
+    }
+
+    /// Where the errors of an instruction are reported.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct Sources {
+        pub span: Span,
+        pub moves: Option<Span>,
+    }
+
+    impl Sources {
+        /// The span of an error of `kind`. The pointer leaving the tape is
+        /// the fault of the moves folded into the offset, when there are.
+        pub fn of(&self, kind: &bferror::error::RuntimeErrorKind) -> Span {
+            match (kind, self.moves) {
+                (
+                    bferror::error::RuntimeErrorKind::PointerUnderflow
+                    | bferror::error::RuntimeErrorKind::PointerOverflow,
+                    Some(moves),
+                ) => moves,
+                _ => self.span,
+            }
+        }
     }
 
     pub struct IRStruct {
-        tokens: RefCell<Vec<TOKEN>>,
-        tmp_results: RefCell<Vec<RefCell<Vec<BFIR>>>>,
-        result: RefCell<Vec<BFIR>>,
+        tokens: RefCell<Vec<Token>>,
+        // bodies of the loops being lowered, with the span of their `[`
+        tmp_results: RefCell<Vec<(Span, RefCell<Vec<IRNode>>)>>,
+        result: RefCell<Vec<IRNode>>,
     }
 
     trait IRInterface {
-        fn new(tokens: &Vec<TOKEN>) -> Self;
-        fn init(&self, tokens: &Vec<TOKEN>);
+        fn new(tokens: &Vec<Token>) -> Self;
         fn clear(&self, clear_result: bool, clear_tokens: bool);
-        fn get_tokens(&self) -> Ref<'_, Vec<TOKEN>>;
-        fn get_result(&self) -> Ref<'_, Vec<BFIR>>;
-        fn stack_start(&self);
-        fn stack_end(&self) -> Result<(), bferror::error::RuntimeError>;
-        fn tmp_push(&self, ir: BFIR) -> Result<(), bferror::error::RuntimeError>;
+        fn get_tokens(&self) -> Ref<'_, Vec<Token>>;
+        fn get_result(&self) -> Ref<'_, Vec<IRNode>>;
+        fn stack_start(&self, span: Span);
+        fn stack_end(&self, span: Span) -> Result<(), bferror::error::RuntimeError>;
+        fn tmp_push(&self, ir: BFIR, span: Span) -> Result<(), bferror::error::RuntimeError>;
     }
 
     impl IRInterface for IRStruct {
-        fn new(tokens: &Vec<TOKEN>) -> Self {
+        fn new(tokens: &Vec<Token>) -> Self {
             IRStruct {
                 tokens: RefCell::new(tokens.clone()),
                 tmp_results: RefCell::new(vec![]),
@@ -100,13 +253,6 @@ pub mod ir {
             }
         }
 
-        fn init(&self, tokens: &Vec<TOKEN>) {
-            self.clear(true, true);
-            tokens
-                .iter()
-                .for_each(|x| self.tokens.borrow_mut().push(x.clone()));
-        }
-
         fn clear(&self, clear_result: bool, clear_tokens: bool) {
             if clear_tokens {
                 self.tokens.borrow_mut().clear();
@@ -120,27 +266,30 @@ pub mod ir {
             self.tmp_results.borrow_mut().shrink_to_fit();
         }
 
-        fn get_tokens(&self) -> Ref<'_, Vec<TOKEN>> {
+        fn get_tokens(&self) -> Ref<'_, Vec<Token>> {
             return self.tokens.borrow();
         }
 
-        fn get_result(&self) -> Ref<'_, Vec<BFIR>> {
+        fn get_result(&self) -> Ref<'_, Vec<IRNode>> {
             return self.result.borrow();
         }
 
-        fn stack_start(&self) {
-            self.tmp_results.borrow_mut().push(RefCell::new(vec![]));
+        fn stack_start(&self, span: Span) {
+            self.tmp_results
+                .borrow_mut()
+                .push((span, RefCell::new(vec![])));
         }
 
-        fn stack_end(&self) -> Result<(), bferror::error::RuntimeError> {
-            let last = self
-                .tmp_results
-                .borrow_mut()
-                .pop()
-                .ok_or(bferror::error::RuntimeError {
-                    index: 0,
-                    kind: bferror::error::RuntimeErrorKind::OutOfRange,
-                })?;
+        fn stack_end(&self, span: Span) -> Result<(), bferror::error::RuntimeError> {
+            let (start, last) =
+                self.tmp_results
+                    .borrow_mut()
+                    .pop()
+                    .ok_or(bferror::error::RuntimeError {
+                        index: 0,
+                        kind: bferror::error::RuntimeErrorKind::OutOfRange,
+                        span: None,
+                    })?;
             let len = self.tmp_results.borrow().len();
             if len == 0 {
                 self.result.borrow_mut().clone_from(&last.borrow());
@@ -151,81 +300,65 @@ pub mod ir {
                 .borrow_mut()
                 .get_mut(len - 1)
                 .unwrap()
+                .1
                 .borrow_mut()
-                .push(BFIR::Loop(last));
+                .push(IRNode::new(BFIR::Loop(last), start.to(span)));
             return Ok(());
         }
 
-        fn tmp_push(&self, ir: BFIR) -> Result<(), bferror::error::RuntimeError> {
+        fn tmp_push(&self, ir: BFIR, span: Span) -> Result<(), bferror::error::RuntimeError> {
             let len = self.tmp_results.borrow().len();
             if len == 0 {
                 return Err(bferror::error::RuntimeError {
                     index: 0,
                     kind: bferror::error::RuntimeErrorKind::OutOfRange,
+                    span: None,
                 });
             }
             self.tmp_results
                 .borrow_mut()
                 .get_mut(len - 1)
                 .unwrap()
+                .1
                 .borrow_mut()
-                .push(ir);
+                .push(IRNode::new(ir, span));
             return Ok(());
         }
     }
 
-    fn reduce_duplicate_updown(
+    // runs of `+-` and `<>` are merged later by `optimizer::MergePass`
+    fn lower_updown(
         mut index: usize,
         ir_struct: Rc<IRStruct>,
     ) -> Result<usize, bferror::error::RuntimeError> {
-        let mut count: i8 = 0;
         let tokens = ir_struct.get_tokens();
         let len = tokens.len();
         while index < len {
-            match tokens[index] {
-                TOKEN::Increment => {
-                    count += 1;
-                    index += 1;
-                }
-                TOKEN::Decrement => {
-                    count -= 1;
-                    index += 1;
-                }
+            let span = tokens[index].span;
+            match tokens[index].kind {
+                TOKEN::Increment => ir_struct.tmp_push(BFIR::Add(1, 0), span)?,
+                TOKEN::Decrement => ir_struct.tmp_push(BFIR::Sub(1, 0), span)?,
                 _ => break,
             }
-        }
-        if count > 0 {
-            ir_struct.tmp_push(BFIR::Add(count as u8))?;
-        } else if count < 0 {
-            ir_struct.tmp_push(BFIR::Sub((-count) as u8))?;
+            index += 1;
         }
         return Ok(index);
     }
 
-    fn reduce_duplicate_leftright(
+    fn lower_leftright(
         mut index: usize,
         ir_struct: Rc<IRStruct>,
     ) -> Result<usize, bferror::error::RuntimeError> {
-        let mut count = 0;
         let tokens = ir_struct.get_tokens();
         let len = tokens.len();
         while index < len {
-            match tokens[index] {
-                TOKEN::MoveRight => {
-                    count += 1;
-                    index += 1;
-                }
-                TOKEN::MoveLeft => {
-                    count -= 1;
-                    index += 1;
-                }
+            let span = tokens[index].span;
+            match tokens[index].kind {
+                TOKEN::MoveRight => ir_struct.tmp_push(BFIR::MoveRight(1), span)?,
+                TOKEN::MoveLeft => ir_struct.tmp_push(BFIR::MoveLeft(1), span)?,
                 _ => break,
             }
-        }
-        if count > 0 {
-            ir_struct.tmp_push(BFIR::MoveRight(count as u32))?;
-        } else if count < 0 {
-            ir_struct.tmp_push(BFIR::MoveLeft((-count) as u32))?;
+            index += 1;
         }
         return Ok(index);
     }
@@ -237,13 +370,14 @@ pub mod ir {
         let tokens = ir_struct.get_tokens();
         let len = tokens.len();
         while index < len {
-            match tokens[index] {
+            let span = tokens[index].span;
+            match tokens[index].kind {
                 TOKEN::Input => {
-                    ir_struct.tmp_push(BFIR::Input)?;
+                    ir_struct.tmp_push(BFIR::Input(0), span)?;
                     index += 1;
                 }
                 TOKEN::Output => {
-                    ir_struct.tmp_push(BFIR::Output)?;
+                    ir_struct.tmp_push(BFIR::Output(0), span)?;
                     index += 1;
                 }
                 _ => break,
@@ -258,10 +392,12 @@ pub mod ir {
     ) -> Result<usize, bferror::error::RuntimeError> {
         let tokens = ir_struct.get_tokens();
         let len = tokens.len();
+        let mut end = tokens[index].span;
+        ir_struct.stack_start(end);
         index += 1; // jump over '['
-        ir_struct.stack_start();
         while index < len {
-            match tokens[index] {
+            end = tokens[index].span;
+            match tokens[index].kind {
                 TOKEN::RightLoop => {
                     index += 1;
                     break;
@@ -272,7 +408,7 @@ pub mod ir {
                 },
             }
         }
-        ir_struct.stack_end()?;
+        ir_struct.stack_end(end)?;
         return Ok(index);
     }
 
@@ -280,20 +416,21 @@ pub mod ir {
         mut index: usize,
         ir_struct: Rc<IRStruct>,
     ) -> Result<usize, bferror::error::RuntimeError> {
-        match ir_struct.get_tokens()[index] {
-            TOKEN::Increment => match reduce_duplicate_updown(index, ir_struct.clone()) {
+        let kind = ir_struct.get_tokens()[index].kind;
+        match kind {
+            TOKEN::Increment => match lower_updown(index, ir_struct.clone()) {
                 Ok(i) => index = i,
                 Err(e) => return Err(e),
             },
-            TOKEN::Decrement => match reduce_duplicate_updown(index, ir_struct.clone()) {
+            TOKEN::Decrement => match lower_updown(index, ir_struct.clone()) {
                 Ok(i) => index = i,
                 Err(e) => return Err(e),
             },
-            TOKEN::MoveRight => match reduce_duplicate_leftright(index, ir_struct.clone()) {
+            TOKEN::MoveRight => match lower_leftright(index, ir_struct.clone()) {
                 Ok(i) => index = i,
                 Err(e) => return Err(e),
             },
-            TOKEN::MoveLeft => match reduce_duplicate_leftright(index, ir_struct.clone()) {
+            TOKEN::MoveLeft => match lower_leftright(index, ir_struct.clone()) {
                 Ok(i) => index = i,
                 Err(e) => return Err(e),
             },
@@ -313,24 +450,175 @@ pub mod ir {
                 return Err(bferror::error::RuntimeError {
                     index,
                     kind: bferror::error::RuntimeErrorKind::OutOfRange,
+                    span: None,
                 });
             }
         }
         return Ok(index);
     }
 
-    pub fn transfer_to_ir(tokens: &Vec<TOKEN>) -> Result<Vec<BFIR>, bferror::error::RuntimeError> {
+    impl fmt::Display for BFIR {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            match self {
+                BFIR::Add(x, o) => write!(f, "add {} @{}", x, o),
+                BFIR::Sub(x, o) => write!(f, "sub {} @{}", x, o),
+                BFIR::MoveLeft(x) => write!(f, "left {}", x),
+                BFIR::MoveRight(x) => write!(f, "right {}", x),
+                BFIR::Input(o) => write!(f, "input @{}", o),
+                BFIR::Output(o) => write!(f, "output @{}", o),
+                BFIR::Loop(_) => write!(f, "loop"),
+                BFIR::SetZero(o) => write!(f, "zero @{}", o),
+                BFIR::Scan { stride } => write!(f, "scan {:+}", stride),
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => write!(f, "muladd @{} += @{} * {}", base + offset, base, factor),
+            }
+        }
+    }
+
+    /// The instructions in preorder, as numbered in runtime errors.
+    pub fn preorder(irs: &Vec<IRNode>) -> Vec<String> {
+        fn walk(irs: &Vec<IRNode>, result: &mut Vec<String>) {
+            for node in irs {
+                result.push(node.ir.to_string());
+                if let BFIR::Loop(x) = &node.ir {
+                    walk(&x.borrow(), result);
+                }
+            }
+        }
+        let mut result = vec![];
+        walk(irs, &mut result);
+        return result;
+    }
+
+    /// The IR tree, one instruction per line with its preorder position, the
+    /// place of its source and loop bodies indented.
+    pub fn dump(irs: &Vec<IRNode>) -> String {
+        fn walk(irs: &Vec<IRNode>, depth: usize, count: &mut usize, result: &mut String) {
+            for node in irs {
+                let place = format!("{}:{}", node.span.line, node.span.col);
+                *result += &format!(
+                    "{:>6}  {:<8}  {}{}\n",
+                    count,
+                    place,
+                    "  ".repeat(depth),
+                    node.ir
+                );
+                *count += 1;
+                if let BFIR::Loop(x) = &node.ir {
+                    walk(&x.borrow(), depth + 1, count, result);
+                    let place = format!("{}:{}", node.span.end_line, node.span.end_col);
+                    *result += &format!("{:>6}  {:<8}  {}end\n", "", place, "  ".repeat(depth));
+                }
+            }
+        }
+        let mut result = String::new();
+        walk(irs, 0, &mut 0, &mut result);
+        return result;
+    }
+
+    /// Position of `target` in a preorder walk of `irs`, used to report
+    /// the instruction a runtime error comes from.
+    pub fn position(irs: &Vec<IRNode>, target: &IRNode) -> Option<usize> {
+        fn walk(irs: &Vec<IRNode>, target: &IRNode, count: &mut usize) -> bool {
+            for node in irs {
+                if std::ptr::eq(node, target) {
+                    return true;
+                }
+                *count += 1;
+                if let BFIR::Loop(x) = &node.ir {
+                    if walk(&x.borrow(), target, count) {
+                        return true;
+                    }
+                }
+            }
+            return false;
+        }
+        let mut count = 0;
+        if walk(irs, target, &mut count) {
+            return Some(count);
+        }
+        return None;
+    }
+
+    /// Sources of each instruction of `irs`, by preorder position.
+    pub fn sources(irs: &Vec<IRNode>) -> Vec<Sources> {
+        fn walk(irs: &Vec<IRNode>, result: &mut Vec<Sources>) {
+            for node in irs {
+                result.push(node.sources());
+                if let BFIR::Loop(x) = &node.ir {
+                    walk(&x.borrow(), result);
+                }
+            }
+        }
+        let mut result = vec![];
+        walk(irs, &mut result);
+        return result;
+    }
+
+    pub fn transfer_to_ir(
+        tokens: &Vec<Token>,
+    ) -> Result<Vec<IRNode>, bferror::error::RuntimeError> {
         let ir_struct = Rc::new(IRStruct::new(tokens));
         let mut index = 0;
         let len = tokens.len();
-        ir_struct.stack_start();
+        ir_struct.stack_start(Span::default());
         while index < len {
             match normal(index, ir_struct.clone()) {
                 Ok(i) => index = i,
                 Err(e) => return Err(e),
             }
         }
-        ir_struct.stack_end()?;
+        ir_struct.stack_end(Span::default())?;
         return Ok(ir_struct.get_result().clone());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::bfparser::frontend::parser::{self, Span, TOKEN};
+    use crate::bftype::bferror::error::{CompileError, CompileErrorKind};
+
+    /// `(line, col, unclosed)` of `error` and its `others`.
+    fn places(error: &CompileError) -> Vec<(u32, u32, bool)> {
+        return std::iter::once(error)
+            .chain(&error.others)
+            .map(|error| {
+                let unclosed = matches!(error.kind, CompileErrorKind::UnclosedLeftBracket);
+                (error.line, error.col, unclosed)
+            })
+            .collect();
+    }
+
+    #[test]
+    fn parse_keeps_places() {
+        let tokens = parser::parse("+ x\n[>]").unwrap();
+        let kinds: Vec<TOKEN> = tokens.iter().map(|token| token.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TOKEN::Increment,
+                TOKEN::LeftLoop,
+                TOKEN::MoveRight,
+                TOKEN::RightLoop
+            ]
+        );
+        assert_eq!(tokens[0].span, Span::at(1, 1));
+        assert_eq!(tokens[3].span, Span::at(2, 3));
+    }
+
+    #[test]
+    fn parse_reports_one_error() {
+        let error = parser::parse("+[").unwrap_err();
+        assert_eq!(places(&error), [(1, 2, true)]);
+    }
+
+    #[test]
+    fn parse_collects_errors_in_source_order() {
+        let error = parser::parse("[[\n]]]\n]\n[[]").unwrap_err();
+        assert_eq!(places(&error), [(2, 3, false), (3, 1, false), (4, 1, true)]);
+        assert!(error.others.iter().all(|other| other.others.is_empty()));
+    }
+}
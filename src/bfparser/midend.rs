@@ -0,0 +1,910 @@
+pub mod optimizer {
+    use std::cell::RefCell;
+    use std::collections::HashMap;
+
+    use crate::bfparser::frontend::ir::{IRNode, BFIR};
+    use crate::bfparser::frontend::parser::Span;
+    use crate::bftype::bferror::error::{CompileError, CompileErrorKind};
+    use crate::Options;
+
+    /// Merge runs of `+`/`-` on the same cell and runs of `<`/`>`. In
+    /// checked mode only changes in the same direction are merged, so that
+    /// `-+` still overflows on a zero cell.
+    pub fn reduce_merge(irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+        let checked = options.checked_cells;
+        let mut result: Vec<IRNode> = vec![];
+        for node in irs {
+            let (delta, offset) = match &node.ir {
+                BFIR::Add(x, o) => (*x as i64, *o),
+                BFIR::Sub(x, o) => (-(*x as i64), *o),
+                BFIR::MoveLeft(x) | BFIR::MoveRight(x) => {
+                    let mut step = match node.ir {
+                        BFIR::MoveLeft(_) => -(*x as i64),
+                        _ => *x as i64,
+                    };
+                    match result.last().map(|last| &last.ir) {
+                        Some(BFIR::MoveLeft(y)) => step -= *y as i64,
+                        Some(BFIR::MoveRight(y)) => step += *y as i64,
+                        _ => {
+                            result.push(node.clone());
+                            continue;
+                        }
+                    }
+                    let span = result.pop().unwrap().span.to(node.span);
+                    push_move(&mut result, step, span);
+                    continue;
+                }
+                BFIR::Loop(x) => {
+                    let body = RefCell::new(reduce_merge(&x.borrow(), options));
+                    result.push(IRNode::new(BFIR::Loop(body), node.span));
+                    continue;
+                }
+                _ => {
+                    result.push(node.clone());
+                    continue;
+                }
+            };
+            let last = match result.last().map(|last| &last.ir) {
+                Some(BFIR::Add(y, o)) if *o == offset => *y as i64,
+                Some(BFIR::Sub(y, o)) if *o == offset => -(*y as i64),
+                _ => {
+                    result.push(node.clone());
+                    continue;
+                }
+            };
+            // counts wrap at 32 bits, the backends truncate them to the cell width
+            let sum = (last + delta) as i32 as i64;
+            if checked && (sum != last + delta || (last > 0) != (delta > 0)) {
+                result.push(node.clone());
+                continue;
+            }
+            let span = result.pop().unwrap().span.to(node.span);
+            let delta = sum;
+            if delta > 0 {
+                result.push(IRNode::new(BFIR::Add(delta as u32, offset), span));
+            } else if delta < 0 {
+                result.push(IRNode::new(
+                    BFIR::Sub(delta.unsigned_abs() as u32, offset),
+                    span,
+                ));
+            }
+        }
+        return result;
+    }
+
+    fn is_clear_loop(body: &Vec<IRNode>, options: &Options) -> bool {
+        match body.as_slice() {
+            [node] => match node.ir {
+                BFIR::Sub(1, 0) => true,
+                // `[+]` overflows instead of wrapping to zero in checked mode
+                BFIR::Add(1, 0) => !options.checked_cells,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Replace clear loops (`[-]` and `[+]`) by a single `SetZero`.
+    pub fn reduce_set_zero(irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+        let mut result = vec![];
+        for node in irs {
+            match &node.ir {
+                BFIR::Loop(x) => {
+                    let body = reduce_set_zero(&x.borrow(), options);
+                    if is_clear_loop(&body, options) {
+                        result.push(IRNode::new(BFIR::SetZero(0), node.span));
+                    } else {
+                        result.push(IRNode::new(BFIR::Loop(RefCell::new(body)), node.span));
+                    }
+                }
+                _ => result.push(node.clone()),
+            }
+        }
+        return result;
+    }
+
+    /// Net cell changes of a loop body which only adds to cells and returns
+    /// the pointer to where it started, as `(offset, delta)` pairs.
+    fn linear_loop_deltas(body: &Vec<IRNode>) -> Option<Vec<(i64, i64)>> {
+        let mut deltas: Vec<(i64, i64)> = vec![];
+        let mut offset: i64 = 0;
+        for node in body {
+            let (delta, at) = match node.ir {
+                BFIR::Add(x, o) => (x as i64, offset + o),
+                BFIR::Sub(x, o) => (-(x as i64), offset + o),
+                BFIR::MoveLeft(x) => {
+                    offset -= x as i64;
+                    continue;
+                }
+                BFIR::MoveRight(x) => {
+                    offset += x as i64;
+                    continue;
+                }
+                _ => return None,
+            };
+            match deltas.iter_mut().find(|(o, _)| *o == at) {
+                Some((_, d)) => *d += delta,
+                None => deltas.push((at, delta)),
+            }
+        }
+        if offset != 0 {
+            return None;
+        }
+        return Some(deltas);
+    }
+
+    /// Replace loops like `[->+>++<<]`, which decrement the current cell and
+    /// add multiples of it to others, by `MulAdd`s followed by a `SetZero`.
+    pub fn reduce_mul_add(irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+        let mut result = vec![];
+        for node in irs {
+            match &node.ir {
+                BFIR::Loop(x) => {
+                    let body = reduce_mul_add(&x.borrow(), options);
+                    let deltas = linear_loop_deltas(&body).filter(|deltas| {
+                        deltas.iter().any(|&(o, d)| o == 0 && d == -1) && !options.checked_cells
+                    });
+                    match deltas {
+                        Some(deltas) => {
+                            for (offset, factor) in deltas {
+                                if offset != 0 && factor != 0 {
+                                    let ir = BFIR::MulAdd {
+                                        base: 0,
+                                        offset,
+                                        factor,
+                                    };
+                                    result.push(IRNode::new(ir, node.span));
+                                }
+                            }
+                            result.push(IRNode::new(BFIR::SetZero(0), node.span));
+                        }
+                        None => result.push(IRNode::new(BFIR::Loop(RefCell::new(body)), node.span)),
+                    }
+                }
+                _ => result.push(node.clone()),
+            }
+        }
+        return result;
+    }
+
+    /// Replace loops that only move the pointer, like `[>]` and `[<<]`, by a
+    /// `Scan` for the next zero cell.
+    pub fn reduce_scan(irs: &Vec<IRNode>) -> Vec<IRNode> {
+        let mut result = vec![];
+        for node in irs {
+            match &node.ir {
+                BFIR::Loop(x) => {
+                    let body = reduce_scan(&x.borrow());
+                    let ir = match body.as_slice() {
+                        [IRNode {
+                            ir: BFIR::MoveRight(n),
+                            ..
+                        }] => BFIR::Scan { stride: *n as i64 },
+                        [IRNode {
+                            ir: BFIR::MoveLeft(n),
+                            ..
+                        }] => BFIR::Scan {
+                            stride: -(*n as i64),
+                        },
+                        _ => BFIR::Loop(RefCell::new(body)),
+                    };
+                    result.push(IRNode::new(ir, node.span));
+                }
+                _ => result.push(node.clone()),
+            }
+        }
+        return result;
+    }
+
+    fn push_move(result: &mut Vec<IRNode>, offset: i64, span: Span) {
+        if offset > 0 {
+            result.push(IRNode::new(BFIR::MoveRight(offset as u32), span));
+        } else if offset < 0 {
+            result.push(IRNode::new(BFIR::MoveLeft((-offset) as u32), span));
+        }
+    }
+
+    /// Fold pointer moves into the offsets of the cell operations, so that
+    /// the pointer only moves once at the end of each basic block.
+    pub fn reduce_offset(irs: &Vec<IRNode>) -> Vec<IRNode> {
+        let mut result = vec![];
+        let mut offset: i64 = 0;
+        // the moves folded since the pointer last moved
+        let mut moves: Option<Span> = None;
+        // the last of them, blamed when a cell at `offset` is off the tape
+        let mut last_move: Option<Span> = None;
+        for node in irs {
+            let span = node.span;
+            let ir = match &node.ir {
+                BFIR::MoveLeft(x) | BFIR::MoveRight(x) => {
+                    offset += match node.ir {
+                        BFIR::MoveLeft(_) => -(*x as i64),
+                        _ => *x as i64,
+                    };
+                    moves = Some(moves.map_or(span, |moves| moves.to(span)));
+                    last_move = Some(span);
+                    continue;
+                }
+                BFIR::Add(x, o) => BFIR::Add(*x, offset + o),
+                BFIR::Sub(x, o) => BFIR::Sub(*x, offset + o),
+                BFIR::Input(o) => BFIR::Input(offset + o),
+                BFIR::Output(o) => BFIR::Output(offset + o),
+                BFIR::SetZero(o) => BFIR::SetZero(offset + o),
+                BFIR::MulAdd {
+                    base,
+                    offset: o,
+                    factor,
+                } => BFIR::MulAdd {
+                    base: offset + base,
+                    offset: *o,
+                    factor: *factor,
+                },
+                BFIR::Scan { stride } => {
+                    push_move(&mut result, offset, moves.take().unwrap_or(span));
+                    offset = 0;
+                    last_move = None;
+                    BFIR::Scan { stride: *stride }
+                }
+                BFIR::Loop(x) => {
+                    push_move(&mut result, offset, moves.take().unwrap_or(span));
+                    offset = 0;
+                    last_move = None;
+                    BFIR::Loop(RefCell::new(reduce_offset(&x.borrow())))
+                }
+            };
+            let mut node = IRNode::new(ir, span);
+            if offset != 0 {
+                node.moves = last_move;
+            }
+            result.push(node);
+        }
+        if let Some(moves) = moves {
+            push_move(&mut result, offset, moves);
+        }
+        return result;
+    }
+
+    /// Cell values known at compile time, indexed by their position relative
+    /// to where the tracking started. `pos` is the current pointer.
+    struct KnownCells {
+        cells: HashMap<i64, Option<u64>>,
+        // stores to known cells not read since, with the value the cell had
+        // before them when known and their places in the result
+        stores: HashMap<i64, (Option<u64>, Vec<usize>)>,
+        // untracked cells are zero, only true until the first loop runs
+        zero: bool,
+        pos: i64,
+    }
+
+    impl KnownCells {
+        fn new(zero: bool) -> Self {
+            KnownCells {
+                cells: HashMap::new(),
+                stores: HashMap::new(),
+                zero,
+                pos: 0,
+            }
+        }
+
+        fn get(&self, offset: i64) -> Option<u64> {
+            match self.cells.get(&(self.pos + offset)) {
+                Some(value) => *value,
+                None if self.zero => Some(0),
+                None => None,
+            }
+        }
+
+        fn set(&mut self, offset: i64, value: Option<u64>) {
+            if value.is_none() {
+                // the stores are needed to compute the unknown value
+                self.read(offset);
+            }
+            self.cells.insert(self.pos + offset, value);
+        }
+
+        /// Keep the stores to the cell at `offset`, which is read.
+        fn read(&mut self, offset: i64) {
+            self.stores.remove(&(self.pos + offset));
+        }
+
+        /// Forget everything but the current cell, zero after a loop.
+        fn after_loop(&mut self) {
+            *self = KnownCells::new(false);
+            self.set(0, Some(0));
+        }
+    }
+
+    /// Push `node`, which stores `value` into the cell at `offset` holding
+    /// `before`, then replace the stores to that cell not read since by the
+    /// fewest giving `value`, in the places of the first of them.
+    fn fold_store(
+        result: &mut Vec<Option<IRNode>>,
+        known: &mut KnownCells,
+        node: &IRNode,
+        offset: i64,
+        before: Option<u64>,
+        value: u64,
+        options: &Options,
+    ) {
+        let mask = u64::MAX >> (64 - 8 * options.cell_type.bytes());
+        let cell = known.pos + offset;
+        known.cells.insert(cell, Some(value));
+        let (base, places) = known.stores.entry(cell).or_insert((before, vec![]));
+        result.push(Some(node.clone()));
+        places.push(result.len() - 1);
+        // a cell of unknown value is cleared first
+        let from = base.unwrap_or(0);
+        let up = value.wrapping_sub(from) & mask;
+        let down = from.wrapping_sub(value) & mask;
+        // stores can't wrap in checked mode
+        let add = match options.checked_cells {
+            true => value >= from,
+            false => up <= down,
+        };
+        let count = if add { up } else { down };
+        let mut irs = vec![];
+        if base.is_none() {
+            irs.push(BFIR::SetZero(0));
+        }
+        if count > u32::MAX as u64 {
+            return;
+        } else if add && count != 0 {
+            irs.push(BFIR::Add(count as u32, 0));
+        } else if count != 0 {
+            irs.push(BFIR::Sub(count as u32, 0));
+        }
+        if irs.len() > places.len() {
+            return;
+        }
+        let mut kept = vec![];
+        for (index, &place) in places.iter().enumerate() {
+            let old = result[place].take().unwrap();
+            let at = match old.ir {
+                BFIR::Add(_, o) | BFIR::Sub(_, o) | BFIR::SetZero(o) => o,
+                _ => unreachable!(),
+            };
+            let ir = match irs.get(index) {
+                Some(BFIR::SetZero(_)) => BFIR::SetZero(at),
+                Some(BFIR::Add(x, _)) => BFIR::Add(*x, at),
+                Some(BFIR::Sub(x, _)) => BFIR::Sub(*x, at),
+                _ => continue,
+            };
+            let span = match index + 1 == irs.len() {
+                true => old.span.to(node.span),
+                false => old.span,
+            };
+            result[place] = Some(IRNode::new(ir, span));
+            kept.push(place);
+        }
+        *places = kept;
+    }
+
+    /// Push `node`, a move of `step` cells, joined with the move before it
+    /// when only removed loops and stores were in between.
+    fn fold_move(result: &mut Vec<Option<IRNode>>, node: &IRNode, step: i64) {
+        let place = result.iter().rposition(|x| x.is_some());
+        let before = match place.and_then(|place| result[place].as_ref()) {
+            Some(IRNode {
+                ir: BFIR::MoveLeft(y),
+                ..
+            }) => -(*y as i64),
+            Some(IRNode {
+                ir: BFIR::MoveRight(y),
+                ..
+            }) => *y as i64,
+            _ => {
+                result.push(Some(node.clone()));
+                return;
+            }
+        };
+        // the stores still tracked are not in between, so they keep their
+        // places and offsets
+        let place = place.unwrap();
+        let span = result[place].as_ref().unwrap().span.to(node.span);
+        let mut moved = vec![];
+        push_move(&mut moved, before + step, span);
+        result[place] = moved.pop();
+    }
+
+    fn fold_known(irs: &Vec<IRNode>, options: &Options, known: &mut KnownCells) -> Vec<IRNode> {
+        let checked = options.checked_cells;
+        let mask = u64::MAX >> (64 - 8 * options.cell_type.bytes());
+        let mut result = vec![];
+        for node in irs {
+            match &node.ir {
+                BFIR::Add(x, o) => {
+                    let before = known.get(*o);
+                    let value = before.and_then(|v| match v.checked_add(*x as u64) {
+                        Some(v) if checked && v > mask => None,
+                        Some(v) => Some(v & mask),
+                        None if checked => None,
+                        None => Some(v.wrapping_add(*x as u64) & mask),
+                    });
+                    match value {
+                        Some(value) => {
+                            fold_store(&mut result, known, node, *o, before, value, options)
+                        }
+                        None => {
+                            known.set(*o, None);
+                            result.push(Some(node.clone()));
+                        }
+                    }
+                }
+                BFIR::Sub(x, o) => {
+                    let before = known.get(*o);
+                    let value = before.and_then(|v| match v.checked_sub(*x as u64) {
+                        Some(v) => Some(v),
+                        None if checked => None,
+                        None => Some(v.wrapping_sub(*x as u64) & mask),
+                    });
+                    match value {
+                        Some(value) => {
+                            fold_store(&mut result, known, node, *o, before, value, options)
+                        }
+                        None => {
+                            known.set(*o, None);
+                            result.push(Some(node.clone()));
+                        }
+                    }
+                }
+                BFIR::SetZero(o) => {
+                    let before = known.get(*o);
+                    if before != Some(0) {
+                        fold_store(&mut result, known, node, *o, before, 0, options);
+                    }
+                }
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => {
+                    let to = base + offset;
+                    let before = known.get(to);
+                    match known.get(*base) {
+                        Some(0) => (),
+                        Some(v) => {
+                            let delta = v.wrapping_mul(*factor as u64) & mask;
+                            let negated = delta.wrapping_neg() & mask;
+                            let value = before.map(|d| d.wrapping_add(delta) & mask);
+                            let ir = if delta <= u32::MAX as u64 {
+                                BFIR::Add(delta as u32, to)
+                            } else if negated <= u32::MAX as u64 {
+                                BFIR::Sub(negated as u32, to)
+                            } else {
+                                known.read(*base);
+                                known.read(to);
+                                known.set(to, value);
+                                result.push(Some(node.clone()));
+                                continue;
+                            };
+                            let node = IRNode::new(ir, node.span);
+                            match value {
+                                Some(value) => fold_store(
+                                    &mut result,
+                                    known,
+                                    &node,
+                                    to,
+                                    before,
+                                    value,
+                                    options,
+                                ),
+                                None => result.push(Some(node)),
+                            }
+                        }
+                        None => {
+                            known.read(*base);
+                            known.set(to, None);
+                            result.push(Some(node.clone()));
+                        }
+                    }
+                }
+                BFIR::MoveLeft(x) => {
+                    known.pos -= *x as i64;
+                    fold_move(&mut result, node, -(*x as i64));
+                }
+                BFIR::MoveRight(x) => {
+                    known.pos += *x as i64;
+                    fold_move(&mut result, node, *x as i64);
+                }
+                BFIR::Input(o) => {
+                    // `,` leaves the cell unchanged at the end of the input
+                    // with `--eof unchanged`, so it also reads it
+                    known.set(*o, None);
+                    result.push(Some(node.clone()));
+                }
+                BFIR::Output(o) => {
+                    known.read(*o);
+                    result.push(Some(node.clone()));
+                }
+                BFIR::Scan { .. } => {
+                    if known.get(0) != Some(0) {
+                        known.after_loop();
+                        result.push(Some(node.clone()));
+                    }
+                }
+                BFIR::Loop(x) => {
+                    if known.get(0) != Some(0) {
+                        let body = fold_known(&x.borrow(), options, &mut KnownCells::new(false));
+                        known.after_loop();
+                        let ir = BFIR::Loop(RefCell::new(body));
+                        result.push(Some(IRNode::new(ir, node.span)));
+                    }
+                }
+            }
+        }
+        return result.into_iter().flatten().collect();
+    }
+
+    /// Track the known cell values from the start of the program, where
+    /// every cell is zero. Loops which can't run and clears of zero cells
+    /// are removed, `MulAdd`s of known cells become plain adds and stores
+    /// overwritten before being read are dropped.
+    pub fn reduce_fold(irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+        return fold_known(irs, options, &mut KnownCells::new(true));
+    }
+
+    /// An optimisation over the IR, run by the `PassManager`.
+    pub trait Pass {
+        /// Name used by `--pass` and `--no-pass`.
+        fn name(&self) -> &'static str;
+        /// Lowest `-O` level running the pass.
+        fn level(&self) -> u8;
+        fn run(&self, irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode>;
+    }
+
+    pub struct MergePass;
+    pub struct SetZeroPass;
+    pub struct MulAddPass;
+    pub struct ScanPass;
+    pub struct FoldPass;
+    pub struct OffsetPass;
+
+    impl Pass for MergePass {
+        fn name(&self) -> &'static str {
+            "merge"
+        }
+        fn level(&self) -> u8 {
+            1
+        }
+        fn run(&self, irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+            reduce_merge(irs, options)
+        }
+    }
+
+    impl Pass for SetZeroPass {
+        fn name(&self) -> &'static str {
+            "set-zero"
+        }
+        fn level(&self) -> u8 {
+            1
+        }
+        fn run(&self, irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+            reduce_set_zero(irs, options)
+        }
+    }
+
+    impl Pass for MulAddPass {
+        fn name(&self) -> &'static str {
+            "mul-add"
+        }
+        fn level(&self) -> u8 {
+            2
+        }
+        fn run(&self, irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+            reduce_mul_add(irs, options)
+        }
+    }
+
+    impl Pass for ScanPass {
+        fn name(&self) -> &'static str {
+            "scan"
+        }
+        fn level(&self) -> u8 {
+            2
+        }
+        fn run(&self, irs: &Vec<IRNode>, _options: &Options) -> Vec<IRNode> {
+            reduce_scan(irs)
+        }
+    }
+
+    impl Pass for FoldPass {
+        fn name(&self) -> &'static str {
+            "fold"
+        }
+        fn level(&self) -> u8 {
+            3
+        }
+        fn run(&self, irs: &Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+            reduce_fold(irs, options)
+        }
+    }
+
+    impl Pass for OffsetPass {
+        fn name(&self) -> &'static str {
+            "offset"
+        }
+        fn level(&self) -> u8 {
+            3
+        }
+        fn run(&self, irs: &Vec<IRNode>, _options: &Options) -> Vec<IRNode> {
+            reduce_offset(irs)
+        }
+    }
+
+    /// Every pass, in the order they run.
+    pub fn all_passes() -> Vec<Box<dyn Pass>> {
+        vec![
+            Box::new(MergePass),
+            Box::new(SetZeroPass),
+            Box::new(MulAddPass),
+            Box::new(ScanPass),
+            Box::new(FoldPass),
+            Box::new(OffsetPass),
+        ]
+    }
+
+    /// The passes selected by `options.opt_level`, `options.passes` and
+    /// `options.no_passes`, `--no-pass` winning over `--pass`.
+    pub struct PassManager {
+        passes: Vec<Box<dyn Pass>>,
+    }
+
+    impl PassManager {
+        pub fn new(options: &Options) -> Result<Self, CompileError> {
+            let all = all_passes();
+            for name in options.passes.iter().chain(options.no_passes.iter()) {
+                if !all.iter().any(|pass| pass.name() == name) {
+                    return Err(CompileError {
+                        line: 0,
+                        col: 0,
+                        kind: CompileErrorKind::UnknownPass(name.clone()),
+                        others: vec![],
+                    });
+                }
+            }
+            let passes = all
+                .into_iter()
+                .filter(|pass| {
+                    let name = pass.name().to_string();
+                    (pass.level() <= options.opt_level || options.passes.contains(&name))
+                        && !options.no_passes.contains(&name)
+                })
+                .collect();
+            Ok(PassManager { passes })
+        }
+
+        /// Names of the selected passes, in order.
+        pub fn names(&self) -> Vec<&'static str> {
+            self.passes.iter().map(|pass| pass.name()).collect()
+        }
+
+        pub fn run(&self, irs: Vec<IRNode>, options: &Options) -> Vec<IRNode> {
+            let mut irs = irs;
+            for pass in &self.passes {
+                irs = pass.run(&irs, options);
+            }
+            return irs;
+        }
+    }
+
+    pub fn optimize(irs: Vec<IRNode>, options: &Options) -> Result<Vec<IRNode>, CompileError> {
+        let manager = PassManager::new(options)?;
+        return Ok(manager.run(irs, options));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bfparser::frontend::ir::{self, IRNode};
+    use crate::bfparser::frontend::parser::{self, Span};
+    use crate::bfparser::midend::optimizer::*;
+    use crate::Options;
+
+    fn lower(src: &str) -> Vec<IRNode> {
+        return ir::transfer_to_ir(&parser::parse(src).unwrap()).unwrap();
+    }
+
+    fn checked() -> Options {
+        Options {
+            checked_cells: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn merge_runs() {
+        let irs = reduce_merge(&lower("+++-->><<<"), &Options::default());
+        assert_eq!(ir::preorder(&irs), ["add 1 @0", "left 1"]);
+        assert_eq!(irs[0].span, Span::at(1, 1).to(Span::at(1, 5)));
+    }
+
+    #[test]
+    fn merge_cancels_runs() {
+        let irs = reduce_merge(&lower("+-<>[-+]"), &Options::default());
+        assert_eq!(ir::preorder(&irs), ["loop"]);
+    }
+
+    #[test]
+    fn merge_keeps_opposite_changes_when_checked() {
+        let irs = reduce_merge(&lower("-+"), &checked());
+        assert_eq!(ir::preorder(&irs), ["sub 1 @0", "add 1 @0"]);
+        let irs = reduce_merge(&lower("++--[-+]"), &checked());
+        assert_eq!(
+            ir::preorder(&irs),
+            ["add 2 @0", "sub 2 @0", "loop", "sub 1 @0", "add 1 @0"]
+        );
+    }
+
+    #[test]
+    fn set_zero_clear_loops() {
+        let irs = reduce_set_zero(&lower("[-]>[+]>[[-]>]"), &Options::default());
+        assert_eq!(
+            ir::preorder(&irs),
+            ["zero @0", "right 1", "zero @0", "right 1", "loop", "zero @0", "right 1"]
+        );
+        assert_eq!(irs[0].span, Span::at(1, 1).to(Span::at(1, 3)));
+    }
+
+    #[test]
+    fn set_zero_keeps_increment_loop_when_checked() {
+        let irs = reduce_set_zero(&lower("[-][+][--]"), &checked());
+        assert_eq!(
+            ir::preorder(&irs),
+            ["zero @0", "loop", "add 1 @0", "loop", "sub 1 @0", "sub 1 @0"]
+        );
+    }
+
+    #[test]
+    fn mul_add_linear_loops() {
+        let irs = reduce_mul_add(&lower("[->++>+++<<]"), &Options::default());
+        assert_eq!(
+            ir::preorder(&irs),
+            ["muladd @1 += @0 * 2", "muladd @2 += @0 * 3", "zero @0"]
+        );
+        let irs = reduce_mul_add(&lower("[>-<-]"), &Options::default());
+        assert_eq!(ir::preorder(&irs), ["muladd @1 += @0 * -1", "zero @0"]);
+    }
+
+    #[test]
+    fn mul_add_keeps_other_loops() {
+        // the pointer doesn't come back, the counter doesn't step by one, io
+        for src in ["[->+]", "[-->+<]", "[->.<]"] {
+            let irs = reduce_mul_add(&lower(src), &Options::default());
+            assert_eq!(ir::preorder(&irs)[0], "loop", "{}", src);
+        }
+        let irs = reduce_mul_add(&lower("[->+<]"), &checked());
+        assert_eq!(ir::preorder(&irs)[0], "loop");
+    }
+
+    #[test]
+    fn offset_folds_moves() {
+        let irs = reduce_merge(&lower(">+>-<.<<"), &Options::default());
+        let irs = reduce_offset(&irs);
+        assert_eq!(
+            ir::preorder(&irs),
+            ["add 1 @1", "sub 1 @2", "output @1", "left 1"]
+        );
+        assert_eq!(irs[3].span, Span::at(1, 1).to(Span::at(1, 8)));
+    }
+
+    #[test]
+    fn offset_moves_before_loops() {
+        let irs = reduce_offset(&lower(">+[<]>>"));
+        assert_eq!(
+            ir::preorder(&irs),
+            ["add 1 @1", "right 1", "loop", "left 1", "right 2"]
+        );
+        let irs = reduce_offset(&lower(">>[-]"));
+        assert_eq!(ir::preorder(&irs), ["right 2", "loop", "sub 1 @0"]);
+    }
+
+    #[test]
+    fn scan_pointer_loops() {
+        let irs = reduce_merge(&lower("[>][<<<]+[[>>]-]"), &Options::default());
+        let irs = reduce_scan(&irs);
+        assert_eq!(
+            ir::preorder(&irs),
+            ["scan +1", "scan -3", "add 1 @0", "loop", "scan +2", "sub 1 @0"]
+        );
+    }
+
+    #[test]
+    fn scan_keeps_other_loops() {
+        let irs = reduce_scan(&lower("[>+][>>]"));
+        // moves are only counted once merged
+        assert_eq!(
+            ir::preorder(&irs),
+            ["loop", "right 1", "add 1 @0", "loop", "right 1", "right 1"]
+        );
+    }
+
+    fn names(options: &Options) -> Vec<&'static str> {
+        return PassManager::new(options).unwrap().names();
+    }
+
+    #[test]
+    fn pass_manager_levels() {
+        let level = |opt_level| Options {
+            opt_level,
+            ..Default::default()
+        };
+        assert!(names(&level(0)).is_empty());
+        assert_eq!(names(&level(1)), ["merge", "set-zero"]);
+        assert_eq!(names(&level(2)), ["merge", "set-zero", "mul-add", "scan"]);
+        assert_eq!(
+            names(&level(3)),
+            ["merge", "set-zero", "mul-add", "scan", "fold", "offset"]
+        );
+    }
+
+    #[test]
+    fn pass_manager_toggles() {
+        let options = Options {
+            opt_level: 1,
+            passes: vec!["scan".to_string(), "merge".to_string()],
+            no_passes: vec!["merge".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(names(&options), ["set-zero", "scan"]);
+        let options = Options {
+            passes: vec!["inline".to_string()],
+            ..Default::default()
+        };
+        assert!(PassManager::new(&options).is_err());
+    }
+
+    fn fold(src: &str) -> Vec<String> {
+        let options = Options::default();
+        let irs = reduce_set_zero(&reduce_merge(&lower(src), &options), &options);
+        return ir::preorder(&reduce_fold(&irs, &options));
+    }
+
+    #[test]
+    fn fold_dead_loops_and_stores() {
+        assert_eq!(fold("[->+<]+[-]>[-]"), ["right 1"]);
+        assert_eq!(
+            fold("++++>++<[-]++"),
+            ["right 1", "add 2 @0", "left 1", "add 2 @0"]
+        );
+    }
+
+    #[test]
+    fn fold_keeps_stores_read() {
+        assert_eq!(fold("+++.[-]+"), ["add 3 @0", "output @0", "sub 2 @0"]);
+        assert_eq!(fold(",[-]++"), ["input @0", "zero @0", "add 2 @0"]);
+        assert_eq!(fold(",[-]++[-]"), ["input @0", "zero @0"]);
+        assert_eq!(
+            fold(",+[-<+>]"),
+            ["input @0", "add 1 @0", "loop", "sub 1 @0", "left 1", "add 1 @0", "right 1"]
+        );
+    }
+
+    #[test]
+    fn fold_known_mul_add() {
+        let options = Options::default();
+        let irs = optimize(lower("+++[->++<]>."), &options).unwrap();
+        assert_eq!(ir::preorder(&irs), ["add 6 @1", "output @1", "right 1"]);
+    }
+
+    #[test]
+    fn fold_joins_moves_around_dead_loops() {
+        assert_eq!(fold("+>[<+>-]<."), ["add 1 @0", "output @0"]);
+        assert_eq!(fold(">>[>]<+"), ["right 1", "add 1 @0"]);
+        let options = Options {
+            no_passes: vec!["offset".to_string()],
+            ..Default::default()
+        };
+        let irs = optimize(lower("+>[-<+>]<.>>[<]<<-"), &options).unwrap();
+        assert_eq!(ir::preorder(&irs), ["add 1 @0", "output @0", "sub 1 @0"]);
+    }
+
+    #[test]
+    fn fold_leaves_merging_to_merge_pass() {
+        let options = Options {
+            no_passes: vec!["merge".to_string()],
+            ..Default::default()
+        };
+        let irs = optimize(lower(",++"), &options).unwrap();
+        assert_eq!(ir::preorder(&irs), ["input @0", "add 1 @0", "add 1 @0"]);
+    }
+}
@@ -1,8 +1,73 @@
 pub mod bfcate {
-    #[allow(dead_code)]
-    #[derive(Clone, Debug, PartialEq)]
+    #[derive(Clone, Debug, Default, PartialEq)]
     pub enum VMArchType {
+        #[default]
         X64,
         X32,
+        AArch64,
+    }
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    pub enum VMBackendType {
+        #[default]
+        Auto,
+        JIT,
+        Interp,
+        /// The wasm module of `wasmgen`, run in an embedded interpreter.
+        Wasm,
+    }
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub enum VMCellType {
+        #[default]
+        U8,
+        U16,
+        U32,
+        U64,
+    }
+
+    impl VMCellType {
+        /// Width of a cell in bytes.
+        pub fn bytes(&self) -> usize {
+            match self {
+                VMCellType::U8 => 1,
+                VMCellType::U16 => 2,
+                VMCellType::U32 => 4,
+                VMCellType::U64 => 8,
+            }
+        }
+    }
+
+    /// What `,` does when the input is exhausted.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    pub enum VMEofType {
+        /// Raise an IO error.
+        #[default]
+        Error,
+        /// Store 0 in the cell.
+        Zero,
+        /// Store -1 (all bits set) in the cell.
+        MinusOne,
+        /// Leave the cell unchanged.
+        Unchanged,
+    }
+
+    /// What `--emit` prints instead of running the program.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum VMEmitType {
+        /// The token stream of the parser.
+        Tokens,
+        /// The optimised IR tree.
+        IR,
+        /// A disassembly of the generated code, annotated with the IR.
+        Asm,
+        /// A standalone C program.
+        C,
+        /// A WebAssembly module in text format.
+        Wat,
+        /// A binary WebAssembly module.
+        Wasm,
+        /// Textual LLVM IR.
+        LLVM,
     }
 }
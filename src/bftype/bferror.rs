@@ -1,17 +1,22 @@
 pub mod error {
+    use crate::bfparser::frontend::parser::Span;
     use std::fmt;
 
     #[derive(Debug, thiserror::Error)]
-    #[allow(dead_code)]
     pub enum CompileErrorKind {
         #[error("Unclosed left bracket")]
         UnclosedLeftBracket,
         #[error("Unexpected right bracket")]
         UnexpectedRightBracket,
+        #[error("Unknown pass `{0}`")]
+        UnknownPass(String),
+        #[error("{0}")]
+        Unsupported(&'static str),
+        #[error("Internal error: {0}")]
+        Internal(RuntimeErrorKind),
     }
 
     #[derive(Debug, thiserror::Error)]
-    #[allow(dead_code)]
     pub enum RuntimeErrorKind {
         #[error("IO Error")]
         IO,
@@ -19,32 +24,217 @@ pub mod error {
         OutOfRange,
         #[error("Memory error")]
         Memory,
+        /// The pointer moved left of the first cell.
+        #[error("Pointer moved below cell 0")]
+        PointerUnderflow,
+        /// The pointer moved right of the last cell.
+        #[error("Pointer moved past the last cell")]
+        PointerOverflow,
+        #[error("Cell overflow")]
+        CellOverflow,
+        /// An option the backend can't generate code for.
+        #[error("{0}")]
+        Unsupported(&'static str),
         #[error("Unknown error")]
         Unknown,
     }
 
+    /// An error at `line`:`col` of the source, both counted from 1, or 0
+    /// when it has no place in the source.
     #[derive(Debug)]
     pub struct CompileError {
         pub line: u32,
         pub col: u32,
         pub kind: CompileErrorKind,
+        /// Further errors found along with this one, in source order.
+        pub others: Vec<CompileError>,
     }
 
+    /// An error of a running program. `span` is the source of the
+    /// instruction it comes from, when the backend can tell.
     #[derive(Debug)]
     pub struct RuntimeError {
         pub index: usize,
         pub kind: RuntimeErrorKind,
+        pub span: Option<Span>,
+    }
+
+    impl RuntimeError {
+        /// Place the error at the instruction `index` coming from `span`,
+        /// unless an instruction nested in that one already placed it.
+        pub fn at(self, index: usize, span: Span) -> Self {
+            if self.span.is_some() {
+                return self;
+            }
+            RuntimeError {
+                index,
+                span: Some(span),
+                ..self
+            }
+        }
+
+        /// Format the error the way `CompileError::render` does, quoting the
+        /// start of the instruction it comes from.
+        pub fn render(&self, src: &str, name: &str) -> String {
+            let lines: Vec<&str> = src.lines().collect();
+            let mut result = format!("error: {}\n", self.kind);
+            if let Some(span) = self.span {
+                result += &snippet(&lines, name, span.line, span.col, "");
+            }
+            return result;
+        }
+    }
+
+    impl CompileError {
+        pub fn internal(err: RuntimeError) -> Self {
+            let kind = match err.kind {
+                RuntimeErrorKind::Unsupported(what) => CompileErrorKind::Unsupported(what),
+                kind => CompileErrorKind::Internal(kind),
+            };
+            CompileError {
+                line: 0,
+                col: 0,
+                kind,
+                others: vec![],
+            }
+        }
+
+        /// Format this error and the `others` the way rustc does, quoting
+        /// the lines of `src` they point at. `name` is the file shown in the
+        /// locations.
+        pub fn render(&self, src: &str, name: &str) -> String {
+            let lines: Vec<&str> = src.lines().collect();
+            let errors: Vec<&CompileError> = std::iter::once(self).chain(&self.others).collect();
+            // all unclosed brackets share one note, after the last of them
+            let last_unclosed = errors
+                .iter()
+                .rposition(|error| matches!(error.kind, CompileErrorKind::UnclosedLeftBracket));
+            let mut result = String::new();
+            for (index, error) in errors.iter().enumerate() {
+                result += &format!("error: {}\n", error.kind);
+                if error.line != 0 {
+                    let label = match error.kind {
+                        CompileErrorKind::UnclosedLeftBracket => "this `[` is never closed",
+                        CompileErrorKind::UnexpectedRightBracket => "no `[` to close",
+                        _ => "",
+                    };
+                    result += &snippet(&lines, name, error.line, error.col, label);
+                }
+                if last_unclosed == Some(index) {
+                    // point just after the last character of the file
+                    let line = lines.len().max(1) as u32;
+                    let col = lines.last().map_or(0, |x| x.chars().count()) as u32 + 1;
+                    result += "note: the file ends here\n";
+                    result += &snippet(&lines, name, line, col, "");
+                }
+                // errors without a place have no snippet to separate
+                if error.line != 0 {
+                    result += "\n";
+                }
+            }
+            if errors.len() > 1 {
+                result += &format!("error: aborting due to {} previous errors\n", errors.len());
+            }
+            return result;
+        }
+    }
+
+    /// Quote line `line` of `lines` with a caret under column `col`.
+    fn snippet(lines: &[&str], name: &str, line: u32, col: u32, label: &str) -> String {
+        let text = lines.get(line as usize - 1).copied().unwrap_or("");
+        let gutter = " ".repeat(line.to_string().len());
+        // keep tabs so that the caret lines up with the quoted line
+        let pad: String = text
+            .chars()
+            .chain(std::iter::repeat(' '))
+            .take(col as usize - 1)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        let mut result = format!("{}--> {}:{}:{}\n", gutter, name, line, col);
+        result += &format!("{} |\n", gutter);
+        result += &format!("{} | {}\n", line, text);
+        result += format!("{} | {}^ {}", gutter, pad, label).trim_end();
+        result += "\n";
+        return result;
     }
 
     impl fmt::Display for CompileError {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{} at line {}:{}", self.kind, self.line, self.col)
+            match self.line {
+                // errors of the options or the backends have no place in the source
+                0 => write!(f, "{}", self.kind)?,
+                _ => write!(f, "{} at line {}:{}", self.kind, self.line, self.col)?,
+            }
+            for other in &self.others {
+                write!(f, "\n{}", other)?;
+            }
+            Ok(())
         }
     }
 
     impl fmt::Display for RuntimeError {
         fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-            write!(f, "{} at index {}", self.kind, self.index)
+            match self.span {
+                Some(span) => write!(f, "{} at {}", self.kind, span),
+                None => write!(f, "{} at index {}", self.kind, self.index),
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::bfparser::frontend::parser;
+    use crate::bftype::bferror::error::{CompileError, RuntimeError, RuntimeErrorKind};
+
+    #[test]
+    fn render_unexpected_bracket() {
+        let error = parser::parse("+]").unwrap_err();
+        assert_eq!(
+            error.render("+]", "a.bf"),
+            "error: Unexpected right bracket\n \
+             --> a.bf:1:2\n  \
+             |\n\
+             1 | +]\n  \
+             |  ^ no `[` to close\n\
+             \n"
+        );
+    }
+
+    #[test]
+    fn render_several_errors() {
+        let src = "]\n[";
+        let error = parser::parse(src).unwrap_err();
+        assert_eq!(
+            error.render(src, "a.bf"),
+            "error: Unexpected right bracket\n \
+             --> a.bf:1:1\n  \
+             |\n\
+             1 | ]\n  \
+             | ^ no `[` to close\n\
+             \n\
+             error: Unclosed left bracket\n \
+             --> a.bf:2:1\n  \
+             |\n\
+             2 | [\n  \
+             | ^ this `[` is never closed\n\
+             note: the file ends here\n \
+             --> a.bf:2:2\n  \
+             |\n\
+             2 | [\n  \
+             |  ^\n\
+             \n\
+             error: aborting due to 2 previous errors\n"
+        );
+    }
+
+    #[test]
+    fn render_without_place() {
+        let error = CompileError::internal(RuntimeError {
+            index: 1,
+            kind: RuntimeErrorKind::Unsupported("not here"),
+            span: None,
+        });
+        assert_eq!(error.render("+", "a.bf"), "error: not here\n");
+    }
+}
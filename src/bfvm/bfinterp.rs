@@ -0,0 +1,229 @@
+pub mod interp {
+    use std::io::{Read, Write};
+
+    use crate::bfparser::frontend::ir;
+    use crate::bfparser::frontend::ir::{IRNode, BFIR};
+    use crate::bftype::bfcate::bfcate::VMEofType;
+    use crate::bftype::bferror;
+    use crate::bfvm::bfjit::vm;
+    use crate::bfvm::bfjit::vm::OutputBuffer;
+    use crate::Options;
+
+    pub struct InterpStruct<'a> {
+        irs: &'a Vec<IRNode>,
+        // byte offset of the current cell
+        ptr: usize,
+        cell_bytes: usize,
+        eof_type: VMEofType,
+        checked_cells: bool,
+        memory: memmap2::MmapMut,
+        input: Box<dyn Read + 'a>,
+        output: OutputBuffer<'a>,
+        growable: bool,
+        add_48: bool,
+    }
+
+    impl<'a> InterpStruct<'a> {
+        pub fn new(
+            irs: &'a Vec<IRNode>,
+            input: Box<dyn Read + 'a>,
+            output: Box<dyn Write + 'a>,
+            options: &Options,
+        ) -> Result<Self, bferror::error::RuntimeError> {
+            let memory = vm::alloc_memory(vm::tape_bytes(options)?)?;
+            Ok(Self {
+                irs,
+                ptr: 0,
+                cell_bytes: options.cell_type.bytes(),
+                eof_type: options.eof_type,
+                checked_cells: options.checked_cells,
+                memory,
+                input,
+                output: OutputBuffer::new(output, options.output_buffer),
+                growable: options.growable,
+                add_48: options.add_48,
+            })
+        }
+
+        fn error(kind: bferror::error::RuntimeErrorKind) -> bferror::error::RuntimeError {
+            bferror::error::RuntimeError {
+                index: 1,
+                kind,
+                span: None,
+            }
+        }
+
+        fn load(&self, at: usize) -> u64 {
+            let mut buf = [0_u8; 8];
+            buf[..self.cell_bytes].copy_from_slice(&self.memory[at..at + self.cell_bytes]);
+            return u64::from_le_bytes(buf);
+        }
+
+        fn store(&mut self, at: usize, value: u64) {
+            let buf = value.to_le_bytes();
+            self.memory[at..at + self.cell_bytes].copy_from_slice(&buf[..self.cell_bytes]);
+        }
+
+        fn cell_max(&self) -> u64 {
+            return u64::MAX >> (64 - 8 * self.cell_bytes);
+        }
+
+        fn move_to(&mut self, offset: isize) -> Result<(), bferror::error::RuntimeError> {
+            if offset >= 0 && (offset as usize) < self.memory.len() {
+                self.ptr = offset as usize;
+                return Ok(());
+            }
+            if !self.growable {
+                return Err(vm::tape_error(offset < 0));
+            }
+            let (memory, left) = vm::grow_memory(&self.memory, offset)?;
+            self.memory = memory;
+            self.ptr = (offset + left as isize) as usize;
+            return Ok(());
+        }
+
+        // byte index of the cell `offset` cells away from the pointer
+        fn cell_at(&mut self, offset: i64) -> Result<usize, bferror::error::RuntimeError> {
+            if offset == 0 {
+                return Ok(self.ptr);
+            }
+            let ptr = self.ptr;
+            let step = offset as isize * self.cell_bytes as isize;
+            self.move_to(ptr as isize + step)?;
+            let at = self.ptr;
+            // growing to the left shifts the current cell as well
+            self.ptr = (at as isize - step) as usize;
+            return Ok(at);
+        }
+
+        fn read_byte(&mut self, at: usize) -> Result<(), bferror::error::RuntimeError> {
+            self.output.flush()?;
+            let mut buf = [0_u8];
+            match vm::read_input(&mut self.input, &mut buf) {
+                Ok(1) => {
+                    let byte = if self.add_48 {
+                        buf[0].wrapping_sub(48)
+                    } else {
+                        buf[0]
+                    };
+                    self.store(at, byte as u64);
+                    return Ok(());
+                }
+                Ok(0) if self.eof_type != VMEofType::Error => {
+                    match self.eof_type {
+                        VMEofType::Zero => self.store(at, 0),
+                        VMEofType::MinusOne => self.store(at, u64::MAX),
+                        _ => (),
+                    }
+                    return Ok(());
+                }
+                _ => return Err(Self::error(bferror::error::RuntimeErrorKind::IO)),
+            }
+        }
+
+        fn write_byte(&mut self, at: usize) -> Result<(), bferror::error::RuntimeError> {
+            let byte = if self.add_48 {
+                (self.load(at) as u8).wrapping_add(48)
+            } else {
+                self.load(at) as u8
+            };
+            return self.output.write_byte(byte);
+        }
+
+        fn run_normal(&mut self, irs: &Vec<IRNode>) -> Result<(), bferror::error::RuntimeError> {
+            for node in irs {
+                self.run_ir(&node.ir).map_err(|e| {
+                    let index = ir::position(self.irs, node).unwrap_or(0);
+                    let span = node.sources().of(&e.kind);
+                    e.at(index, span)
+                })?;
+            }
+            return Ok(());
+        }
+
+        fn run_ir(&mut self, ir: &BFIR) -> Result<(), bferror::error::RuntimeError> {
+            match ir {
+                BFIR::Add(x, o) if self.checked_cells => {
+                    let at = self.cell_at(*o)?;
+                    match self.load(at).checked_add(*x as u64) {
+                        Some(value) if value <= self.cell_max() => self.store(at, value),
+                        _ => {
+                            return Err(Self::error(bferror::error::RuntimeErrorKind::CellOverflow))
+                        }
+                    }
+                }
+                BFIR::Sub(x, o) if self.checked_cells => {
+                    let at = self.cell_at(*o)?;
+                    match self.load(at).checked_sub(*x as u64) {
+                        Some(value) => self.store(at, value),
+                        None => {
+                            return Err(Self::error(bferror::error::RuntimeErrorKind::CellOverflow))
+                        }
+                    }
+                }
+                BFIR::Add(x, o) => {
+                    let at = self.cell_at(*o)?;
+                    self.store(at, self.load(at).wrapping_add(*x as u64));
+                }
+                BFIR::Sub(x, o) => {
+                    let at = self.cell_at(*o)?;
+                    self.store(at, self.load(at).wrapping_sub(*x as u64));
+                }
+                BFIR::MoveLeft(x) => {
+                    self.move_to(self.ptr as isize - (*x as usize * self.cell_bytes) as isize)?
+                }
+                BFIR::MoveRight(x) => {
+                    self.move_to(self.ptr as isize + (*x as usize * self.cell_bytes) as isize)?
+                }
+                BFIR::SetZero(o) => {
+                    let at = self.cell_at(*o)?;
+                    self.store(at, 0);
+                }
+                BFIR::MulAdd {
+                    base,
+                    offset,
+                    factor,
+                } => {
+                    let at = self.cell_at(*base)?;
+                    let value = self.load(at);
+                    if value != 0 {
+                        let to = self.cell_at(base + offset)?;
+                        self.store(
+                            to,
+                            self.load(to)
+                                .wrapping_add(value.wrapping_mul(*factor as u64)),
+                        );
+                    }
+                }
+                BFIR::Scan { stride } => {
+                    let step = *stride as isize * self.cell_bytes as isize;
+                    match vm::scan_memory(&self.memory, self.ptr, step, self.cell_bytes) {
+                        Ok(at) => self.ptr = at,
+                        Err(offset) => self.move_to(offset)?,
+                    }
+                }
+                BFIR::Input(o) => {
+                    let at = self.cell_at(*o)?;
+                    self.read_byte(at)?
+                }
+                BFIR::Output(o) => {
+                    let at = self.cell_at(*o)?;
+                    self.write_byte(at)?
+                }
+                BFIR::Loop(x) => {
+                    while self.load(self.ptr) != 0 {
+                        self.run_normal(&x.borrow())?;
+                    }
+                }
+            }
+            return Ok(());
+        }
+
+        pub fn run(&mut self) -> Result<(), bferror::error::RuntimeError> {
+            let irs = self.irs;
+            let result = self.run_normal(irs);
+            let flushed = self.output.flush();
+            return result.and(flushed);
+        }
+    }
+}
@@ -1,46 +1,337 @@
 pub mod vm {
-    use dynasmrt::relocations::Relocation;
-    use dynasmrt::{Assembler, AssemblyOffset};
+    use dynasmrt::AssemblyOffset;
     use std::io::{Read, Write};
     use std::ptr;
 
-    use crate::bftype::bfcate;
-    use crate::bftype::bfcate::bfcate::VMArchType;
+    use crate::bfparser::backend::codegen::CodeMap;
+    use crate::bftype::bfcate::bfcate::{VMArchType, VMCellType, VMEofType};
     use crate::bftype::bferror;
+    use crate::Options;
 
-    const MEMORY_SIZE: usize = 30000;
+    /// Default number of cells on the tape.
+    pub const MEMORY_SIZE: usize = 30000;
+    /// Default number of bytes kept by an `OutputBuffer`.
+    pub const OUTPUT_BUFFER_SIZE: usize = 8192;
+    /// Number of bytes read ahead for `,` by the x64 JIT of the command line.
+    pub const INPUT_BUFFER_SIZE: usize = 4096;
 
+    /// Whether the JIT code of `options` reads `,` from and writes `.` to the
+    /// buffers of `VMStruct`, calling back only when they are empty or full.
+    /// Only the x64 JIT does, and never in unbuffered mode.
+    pub fn batched_io(options: &Options) -> bool {
+        options.vm_arch_type == VMArchType::X64 && options.output_buffer > 0
+    }
+
+    /// `Read::read` retried for as long as it is interrupted by a signal.
+    pub fn read_input(input: &mut dyn Read, buf: &mut [u8]) -> std::io::Result<usize> {
+        loop {
+            match input.read(buf) {
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                result => return result,
+            }
+        }
+    }
+
+    /// Error of a JIT for `vm_arch_type` generated or run on another host.
+    pub fn wrong_host(vm_arch_type: &VMArchType) -> bferror::error::RuntimeError {
+        let message = match vm_arch_type {
+            VMArchType::X64 => "the x64 JIT needs an x86_64 host",
+            VMArchType::X32 => "the x32 JIT needs an x86 host",
+            VMArchType::AArch64 => "the aarch64 JIT needs an aarch64 host",
+        };
+        return bferror::error::RuntimeError {
+            index: 1,
+            kind: bferror::error::RuntimeErrorKind::Unsupported(message),
+            span: None,
+        };
+    }
+
+    /// Output of a running program. Bytes are held until a newline, until
+    /// `capacity` of them are pending or until `flush`, which the runners
+    /// call before reading input and when the program stops. A capacity of 0
+    /// writes every byte as it comes.
+    pub struct OutputBuffer<'a> {
+        output: Box<dyn Write + 'a>,
+        buffer: Vec<u8>,
+        capacity: usize,
+    }
+
+    impl<'a> OutputBuffer<'a> {
+        pub fn new(output: Box<dyn Write + 'a>, capacity: usize) -> Self {
+            Self {
+                output,
+                buffer: Vec::with_capacity(capacity),
+                capacity,
+            }
+        }
+
+        pub fn write_byte(&mut self, byte: u8) -> Result<(), bferror::error::RuntimeError> {
+            if self.capacity == 0 {
+                return self.output.write_all(&[byte]).map_err(|_| Self::error());
+            }
+            self.buffer.push(byte);
+            if byte == b'\n' || self.buffer.len() >= self.capacity {
+                return self.flush();
+            }
+            return Ok(());
+        }
+
+        /// `write_byte` for a run of bytes, flushed once if it holds a newline.
+        pub fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), bferror::error::RuntimeError> {
+            if self.capacity == 0 {
+                return self.output.write_all(bytes).map_err(|_| Self::error());
+            }
+            self.buffer.extend_from_slice(bytes);
+            if memchr::memchr(b'\n', bytes).is_some() || self.buffer.len() >= self.capacity {
+                return self.flush();
+            }
+            return Ok(());
+        }
+
+        /// Write the pending bytes and flush the underlying output.
+        pub fn flush(&mut self) -> Result<(), bferror::error::RuntimeError> {
+            let result = self
+                .output
+                .write_all(&self.buffer)
+                .and_then(|_| self.output.flush());
+            self.buffer.clear();
+            return result.map_err(|_| Self::error());
+        }
+
+        fn error() -> bferror::error::RuntimeError {
+            bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::IO,
+                span: None,
+            }
+        }
+    }
+
+    /// Allocate a zeroed tape of `size` cells. Pages are mapped anonymously
+    /// and only committed when touched, so large tapes are cheap.
+    pub fn alloc_memory(size: usize) -> Result<memmap2::MmapMut, bferror::error::RuntimeError> {
+        if size == 0 {
+            return Err(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+        }
+        return memmap2::MmapMut::map_anon(size).map_err(|_| bferror::error::RuntimeError {
+            index: 1,
+            kind: bferror::error::RuntimeErrorKind::Memory,
+            span: None,
+        });
+    }
+
+    /// Size in bytes of the initial tape described by `options`.
+    pub fn tape_bytes(options: &Options) -> Result<usize, bferror::error::RuntimeError> {
+        return options
+            .tape_size
+            .checked_mul(options.cell_type.bytes())
+            .ok_or(bferror::error::RuntimeError {
+                index: 1,
+                kind: bferror::error::RuntimeErrorKind::Memory,
+                span: None,
+            });
+    }
+
+    /// Error of the pointer leaving a tape which can't grow, on the left
+    /// when `below`.
+    pub fn tape_error(below: bool) -> bferror::error::RuntimeError {
+        let kind = if below {
+            bferror::error::RuntimeErrorKind::PointerUnderflow
+        } else {
+            bferror::error::RuntimeErrorKind::PointerOverflow
+        };
+        return bferror::error::RuntimeError {
+            index: 1,
+            kind,
+            span: None,
+        };
+    }
+
+    /// Grow `memory` so that the cell at the signed `offset` fits, at least
+    /// doubling its size. Returns the new memory and the number of cells
+    /// added on the left.
+    pub fn grow_memory(
+        memory: &memmap2::MmapMut,
+        offset: isize,
+    ) -> Result<(memmap2::MmapMut, usize), bferror::error::RuntimeError> {
+        let len = memory.len();
+        let (left, new_len) = if offset < 0 {
+            let left = offset.unsigned_abs().max(len);
+            (left, len.checked_add(left))
+        } else {
+            // whole multiples of the old size keep cells from being cut
+            let times = (offset as usize / len).checked_add(1);
+            (0, times.and_then(|x| x.max(2).checked_mul(len)))
+        };
+        let new_len = new_len.ok_or(bferror::error::RuntimeError {
+            index: 1,
+            kind: bferror::error::RuntimeErrorKind::Memory,
+            span: None,
+        })?;
+        let mut new_memory = alloc_memory(new_len)?;
+        new_memory[left..left + len].copy_from_slice(memory);
+        return Ok((new_memory, left));
+    }
+
+    /// Find the first zero cell from byte `at`, moving `step` bytes at a
+    /// time. On failure returns the first position outside of `memory`.
+    pub fn scan_memory(
+        memory: &[u8],
+        at: usize,
+        step: isize,
+        cell_bytes: usize,
+    ) -> Result<usize, isize> {
+        match (cell_bytes, step) {
+            (1, 1) => {
+                return memchr::memchr(0, &memory[at..])
+                    .map(|x| at + x)
+                    .ok_or(memory.len() as isize)
+            }
+            (1, -1) => return memchr::memrchr(0, &memory[..=at]).ok_or(-1),
+            _ => (),
+        }
+        let mut pos = at as isize;
+        while pos >= 0 && (pos as usize) < memory.len() {
+            let cell = &memory[pos as usize..pos as usize + cell_bytes];
+            if cell.iter().all(|x| *x == 0) {
+                return Ok(pos as usize);
+            }
+            pos += step;
+        }
+        return Err(pos);
+    }
+
+    #[cfg(target_arch = "x86_64")]
     type RawFnX64 = unsafe extern "sysv64" fn(
         this: *mut VMStruct,
         memory_start: *mut u8,
         memory_end: *const u8,
     ) -> *mut bferror::error::RuntimeError;
 
-    pub struct VMStruct {
-        code: dynasmrt::ExecutableBuffer,
+    #[cfg(target_arch = "x86")]
+    type RawFnX32 = unsafe extern "cdecl" fn(
+        this: *mut VMStruct,
+        memory_start: *mut u8,
+        memory_end: *const u8,
+    ) -> *mut bferror::error::RuntimeError;
+
+    #[cfg(target_arch = "aarch64")]
+    type RawFnAArch64 = unsafe extern "C" fn(
+        this: *mut VMStruct,
+        memory_start: *mut u8,
+        memory_end: *const u8,
+    ) -> *mut bferror::error::RuntimeError;
+
+    pub struct VMStruct<'a> {
+        code: &'a dynasmrt::ExecutableBuffer,
+        map: &'a CodeMap,
         pc: dynasmrt::AssemblyOffset,
-        memory: Box<[u8]>,
-        input: Box<dyn Read>,
-        output: Box<dyn Write>,
-        vm_arch_type: bfcate::bfcate::VMArchType,
+        // return address of the last call from the generated code into the
+        // callbacks, 0 until the first one
+        fault: usize,
+        memory: memmap2::MmapMut,
+        // bounds and pointer reloaded by the generated code after a grow
+        tape_start: *mut u8,
+        tape_end: *const u8,
+        tape_ptr: *mut u8,
+        // bytes read ahead for `,` and written by `.` in the generated code,
+        // `in_ptr..in_end` are left to read and `out_ptr..out_end` are free
+        in_ptr: *const u8,
+        in_end: *const u8,
+        out_ptr: *mut u8,
+        out_end: *const u8,
+        in_buf: Vec<u8>,
+        out_buf: Vec<u8>,
+        input: Box<dyn Read + 'a>,
+        output: OutputBuffer<'a>,
+        vm_arch_type: VMArchType,
+        cell_type: VMCellType,
+        eof_type: VMEofType,
+        growable: bool,
         add_48: bool,
     }
 
+    /// Offsets of `tape_start`, `tape_end` and `tape_ptr` inside `VMStruct`.
+    pub fn tape_offsets() -> (i32, i32, i32) {
+        (
+            std::mem::offset_of!(VMStruct, tape_start) as i32,
+            std::mem::offset_of!(VMStruct, tape_end) as i32,
+            std::mem::offset_of!(VMStruct, tape_ptr) as i32,
+        )
+    }
+
+    /// Offset of `fault` inside `VMStruct`.
+    pub fn fault_offset() -> i32 {
+        std::mem::offset_of!(VMStruct, fault) as i32
+    }
+
+    /// Offsets of `in_ptr`, `in_end`, `out_ptr` and `out_end` inside `VMStruct`.
+    pub fn io_offsets() -> (i32, i32, i32, i32) {
+        (
+            std::mem::offset_of!(VMStruct, in_ptr) as i32,
+            std::mem::offset_of!(VMStruct, in_end) as i32,
+            std::mem::offset_of!(VMStruct, out_ptr) as i32,
+            std::mem::offset_of!(VMStruct, out_end) as i32,
+        )
+    }
+
     fn to_raw<R, T>(ptr: T) -> *mut R {
         Box::into_raw(Box::new(ptr)) as *mut R
     }
 
-    impl VMStruct {
-        pub unsafe extern "sysv64" fn put_x64_byte(
-            this: *mut Self,
-            byte_ptr: *mut u8,
-        ) -> *mut bferror::error::RuntimeError {
-            let mut buf = [0_u8];
-            let this = &mut *this;
-            match this.input.read(&mut buf) {
-                Ok(1) => {
-                    let byte = if this.add_48 { buf[0] - 48 } else { buf[0] };
-                    *byte_ptr = byte;
+    impl<'a> VMStruct<'a> {
+        unsafe fn store(&self, byte_ptr: *mut u8, value: u64) {
+            match self.cell_type {
+                VMCellType::U8 => *byte_ptr = value as u8,
+                VMCellType::U16 => *(byte_ptr as *mut u16) = value as u16,
+                VMCellType::U32 => *(byte_ptr as *mut u32) = value as u32,
+                VMCellType::U64 => *(byte_ptr as *mut u64) = value,
+            }
+        }
+
+        /// Hand the bytes written by the generated code to the output.
+        fn flush_output(&mut self) -> Result<(), bferror::error::RuntimeError> {
+            let len = self.out_ptr as usize - self.out_buf.as_ptr() as usize;
+            self.out_ptr = self.out_buf.as_mut_ptr();
+            return self.output.write_bytes(&self.out_buf[..len]);
+        }
+
+        unsafe fn put_byte(&mut self, byte_ptr: *mut u8) -> *mut bferror::error::RuntimeError {
+            // show pending output, such as a prompt, before blocking on input
+            if let Err(e) = self.flush_output().and_then(|_| self.output.flush()) {
+                return to_raw(e);
+            }
+            // refill the read-ahead buffer, or read a single byte without one
+            let mut byte = [0_u8];
+            let buf = if self.in_buf.is_empty() {
+                &mut byte[..]
+            } else {
+                &mut self.in_buf[..]
+            };
+            match read_input(&mut self.input, buf) {
+                Ok(n) if n > 0 => {
+                    let byte = if self.add_48 {
+                        buf[0].wrapping_sub(48)
+                    } else {
+                        buf[0]
+                    };
+                    if !self.in_buf.is_empty() {
+                        self.in_ptr = self.in_buf.as_ptr().add(1);
+                        self.in_end = self.in_buf.as_ptr().add(n);
+                    }
+                    self.store(byte_ptr, byte as u64);
+                    return ptr::null_mut();
+                }
+                Ok(0) if self.eof_type != VMEofType::Error => {
+                    match self.eof_type {
+                        VMEofType::Zero => self.store(byte_ptr, 0),
+                        VMEofType::MinusOne => self.store(byte_ptr, u64::MAX),
+                        _ => (),
+                    }
                     return ptr::null_mut();
                 }
                 _ => {
@@ -48,90 +339,369 @@ pub mod vm {
                         bferror::error::RuntimeError {
                             index: 1,
                             kind: bferror::error::RuntimeErrorKind::IO,
+                            span: None,
                         },
                     )
                 }
             }
         }
 
+        unsafe fn get_byte(&mut self, byte_ptr: *const u8) -> *mut bferror::error::RuntimeError {
+            let byte = if self.add_48 {
+                (*byte_ptr).wrapping_add(48)
+            } else {
+                *byte_ptr
+            };
+            match self.output.write_byte(byte) {
+                Ok(()) => return ptr::null_mut(),
+                Err(e) => return to_raw(e),
+            }
+        }
+
+        /// Callback for `,`, reads one byte from the input into the cell at `byte_ptr`.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `byte_ptr` into its memory.
+        #[cfg(target_arch = "x86_64")]
+        pub unsafe extern "sysv64" fn put_x64_byte(
+            this: *mut Self,
+            byte_ptr: *mut u8,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).put_byte(byte_ptr)
+        }
+
+        /// Callback for `.`, writes the low byte of the cell at `byte_ptr` to the output.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `byte_ptr` into its memory.
+        #[cfg(target_arch = "x86_64")]
         pub unsafe extern "sysv64" fn get_x64_byte(
             this: *mut Self,
             byte_ptr: *const u8,
         ) -> *mut bferror::error::RuntimeError {
-            let this = &mut *this;
-            let byte = if this.add_48 {
-                *byte_ptr + 48
-            } else {
-                *byte_ptr
-            };
-            match this.output.write(&[byte]) {
-                Ok(1) => return ptr::null_mut(),
-                _ => {
-                    return to_raw::<bferror::error::RuntimeError, _>(
-                        bferror::error::RuntimeError {
-                            index: 1,
-                            kind: bferror::error::RuntimeErrorKind::IO,
-                        },
-                    )
+            (*this).get_byte(byte_ptr)
+        }
+
+        /// Called by the generated code when the pointer leaves the memory,
+        /// on the left when `below`.
+        ///
+        /// # Safety
+        /// The returned error must be released with `Box::from_raw`.
+        #[cfg(target_arch = "x86_64")]
+        pub unsafe extern "sysv64" fn overflow_error(
+            below: bool,
+        ) -> *mut bferror::error::RuntimeError {
+            to_raw(tape_error(below))
+        }
+
+        unsafe fn grow(&mut self, offset: isize) -> *mut bferror::error::RuntimeError {
+            if !self.growable {
+                return to_raw(tape_error(offset < 0));
+            }
+            match grow_memory(&self.memory, offset) {
+                Ok((memory, left)) => {
+                    self.memory = memory;
+                    self.tape_start = self.memory.as_mut_ptr();
+                    self.tape_end = self.tape_start.add(self.memory.len());
+                    self.tape_ptr = self.tape_start.offset(offset + left as isize);
+                    return ptr::null_mut();
+                }
+                Err(e) => return to_raw(e),
+            }
+        }
+
+        /// Called by the generated code when the pointer leaves the memory
+        /// in growable mode, `offset` is the pointer relative to the start.
+        /// On success `tape_start`, `tape_end` and `tape_ptr` are updated.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct`.
+        #[cfg(target_arch = "x86_64")]
+        pub unsafe extern "sysv64" fn grow_x64(
+            this: *mut Self,
+            offset: isize,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).grow(offset)
+        }
+
+        unsafe fn scan(&mut self, offset: isize, step: isize) -> *mut bferror::error::RuntimeError {
+            match scan_memory(&self.memory, offset as usize, step, self.cell_type.bytes()) {
+                Ok(at) => {
+                    self.tape_ptr = self.tape_start.add(at);
+                    return ptr::null_mut();
                 }
+                Err(offset) => return self.grow(offset),
+            }
+        }
+
+        /// Callback for `Scan`, moves `tape_ptr` from `offset` to the next
+        /// zero cell `step` bytes at a time, growing the memory if allowed.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `offset` into its memory.
+        #[cfg(target_arch = "x86_64")]
+        pub unsafe extern "sysv64" fn scan_x64(
+            this: *mut Self,
+            offset: isize,
+            step: isize,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).scan(offset, step)
+        }
+
+        /// Called by the generated code when the output buffer is full or
+        /// ends with a newline.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct`.
+        #[cfg(target_arch = "x86_64")]
+        pub unsafe extern "sysv64" fn flush_x64_output(
+            this: *mut Self,
+        ) -> *mut bferror::error::RuntimeError {
+            match (*this).flush_output() {
+                Ok(()) => ptr::null_mut(),
+                Err(e) => to_raw(e),
             }
         }
 
-        pub unsafe fn overflow_error() -> *mut bferror::error::RuntimeError {
+        /// Called by the generated code when an `Add`/`Sub` overflows a cell
+        /// in checked mode, `index` is the position of the instruction.
+        ///
+        /// # Safety
+        /// The returned error must be released with `Box::from_raw`.
+        #[cfg(target_arch = "x86_64")]
+        pub unsafe extern "sysv64" fn cell_overflow_x64(
+            index: usize,
+        ) -> *mut bferror::error::RuntimeError {
             to_raw(bferror::error::RuntimeError {
-                index: 1,
-                kind: bferror::error::RuntimeErrorKind::Memory,
+                index,
+                kind: bferror::error::RuntimeErrorKind::CellOverflow,
+                span: None,
+            })
+        }
+
+        /// cdecl version of `put_x64_byte` for the x32 backend.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `byte_ptr` into its memory.
+        #[cfg(target_arch = "x86")]
+        pub unsafe extern "cdecl" fn put_x32_byte(
+            this: *mut Self,
+            byte_ptr: *mut u8,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).put_byte(byte_ptr)
+        }
+
+        /// cdecl version of `get_x64_byte` for the x32 backend.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `byte_ptr` into its memory.
+        #[cfg(target_arch = "x86")]
+        pub unsafe extern "cdecl" fn get_x32_byte(
+            this: *mut Self,
+            byte_ptr: *const u8,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).get_byte(byte_ptr)
+        }
+
+        /// cdecl version of `overflow_error` for the x32 backend.
+        ///
+        /// # Safety
+        /// The returned error must be released with `Box::from_raw`.
+        #[cfg(target_arch = "x86")]
+        pub unsafe extern "cdecl" fn overflow_x32_error(
+            below: bool,
+        ) -> *mut bferror::error::RuntimeError {
+            to_raw(tape_error(below))
+        }
+
+        /// AAPCS64 version of `put_x64_byte` for the aarch64 backend.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `byte_ptr` into its memory.
+        #[cfg(target_arch = "aarch64")]
+        pub unsafe extern "C" fn put_aarch64_byte(
+            this: *mut Self,
+            byte_ptr: *mut u8,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).put_byte(byte_ptr)
+        }
+
+        /// AAPCS64 version of `get_x64_byte` for the aarch64 backend.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `byte_ptr` into its memory.
+        #[cfg(target_arch = "aarch64")]
+        pub unsafe extern "C" fn get_aarch64_byte(
+            this: *mut Self,
+            byte_ptr: *const u8,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).get_byte(byte_ptr)
+        }
+
+        /// AAPCS64 version of `overflow_error` for the aarch64 backend.
+        ///
+        /// # Safety
+        /// The returned error must be released with `Box::from_raw`.
+        #[cfg(target_arch = "aarch64")]
+        pub unsafe extern "C" fn overflow_aarch64_error(
+            below: bool,
+        ) -> *mut bferror::error::RuntimeError {
+            to_raw(tape_error(below))
+        }
+
+        /// AAPCS64 version of `grow_x64` for the aarch64 backend.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct`.
+        #[cfg(target_arch = "aarch64")]
+        pub unsafe extern "C" fn grow_aarch64(
+            this: *mut Self,
+            offset: isize,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).grow(offset)
+        }
+
+        /// AAPCS64 version of `scan_x64` for the aarch64 backend.
+        ///
+        /// # Safety
+        /// `this` must point to the running `VMStruct` and `offset` into its memory.
+        #[cfg(target_arch = "aarch64")]
+        pub unsafe extern "C" fn scan_aarch64(
+            this: *mut Self,
+            offset: isize,
+            step: isize,
+        ) -> *mut bferror::error::RuntimeError {
+            (*this).scan(offset, step)
+        }
+
+        /// AAPCS64 version of `cell_overflow_x64` for the aarch64 backend.
+        ///
+        /// # Safety
+        /// The returned error must be released with `Box::from_raw`.
+        #[cfg(target_arch = "aarch64")]
+        pub unsafe extern "C" fn cell_overflow_aarch64(
+            index: usize,
+        ) -> *mut bferror::error::RuntimeError {
+            to_raw(bferror::error::RuntimeError {
+                index,
+                kind: bferror::error::RuntimeErrorKind::CellOverflow,
+                span: None,
             })
         }
 
-        pub fn new<T: Relocation + std::fmt::Debug>(
-            ops: Assembler<T>,
-            input: Box<dyn Read>,
-            output: Box<dyn Write>,
-            vm_arch_type: bfcate::bfcate::VMArchType,
-            add_48: bool,
+        pub fn new(
+            code: &'a dynasmrt::ExecutableBuffer,
+            map: &'a CodeMap,
+            input: Box<dyn Read + 'a>,
+            output: Box<dyn Write + 'a>,
+            options: &Options,
         ) -> Result<Self, bferror::error::RuntimeError> {
             let pc = AssemblyOffset(0);
-            let memory = vec![0; MEMORY_SIZE].into_boxed_slice();
+            let mut memory = alloc_memory(tape_bytes(options)?)?;
+            let tape_start = memory.as_mut_ptr();
+            let tape_end = unsafe { tape_start.add(memory.len()) };
+            let (in_buf, mut out_buf) = if batched_io(options) {
+                (
+                    vec![0; options.input_buffer],
+                    vec![0; options.output_buffer],
+                )
+            } else {
+                (vec![], vec![])
+            };
             Ok(Self {
-                code: ops.finalize().unwrap(),
+                code,
+                map,
                 pc,
+                fault: 0,
                 memory,
+                tape_start,
+                tape_end,
+                tape_ptr: tape_start,
+                in_ptr: in_buf.as_ptr(),
+                in_end: in_buf.as_ptr(),
+                out_ptr: out_buf.as_mut_ptr(),
+                out_end: unsafe { out_buf.as_ptr().add(out_buf.len()) },
+                in_buf,
+                out_buf,
                 input,
-                output,
-                vm_arch_type,
-                add_48,
+                output: OutputBuffer::new(output, options.output_buffer),
+                vm_arch_type: options.vm_arch_type.clone(),
+                cell_type: options.cell_type,
+                eof_type: options.eof_type,
+                growable: options.growable,
+                add_48: options.add_48,
             })
         }
 
-        fn run_x64(&mut self) -> Result<(), bferror::error::RuntimeError> {
-            let raw_fn: RawFnX64 = unsafe { std::mem::transmute(self.code.ptr(self.pc)) };
-
+        fn run_raw(&mut self) -> Result<(), bferror::error::RuntimeError> {
             let this: *mut Self = self;
-            let memory_start = self.memory.as_mut_ptr();
-            let memory_end = unsafe { memory_start.add(MEMORY_SIZE) };
+            let memory_start = self.tape_start;
+            let memory_end = self.tape_end;
 
-            let ret: *mut bferror::error::RuntimeError =
-                unsafe { raw_fn(this, memory_start, memory_end) };
+            let ret: *mut bferror::error::RuntimeError = match self.vm_arch_type {
+                #[cfg(target_arch = "x86_64")]
+                VMArchType::X64 => {
+                    let raw_fn: RawFnX64 = unsafe { std::mem::transmute(self.code.ptr(self.pc)) };
+                    unsafe { raw_fn(this, memory_start, memory_end) }
+                }
+                #[cfg(target_arch = "x86")]
+                VMArchType::X32 => {
+                    let raw_fn: RawFnX32 = unsafe { std::mem::transmute(self.code.ptr(self.pc)) };
+                    unsafe { raw_fn(this, memory_start, memory_end) }
+                }
+                #[cfg(target_arch = "aarch64")]
+                VMArchType::AArch64 => {
+                    let raw_fn: RawFnAArch64 =
+                        unsafe { std::mem::transmute(self.code.ptr(self.pc)) };
+                    unsafe { raw_fn(this, memory_start, memory_end) }
+                }
+                #[cfg(not(target_arch = "x86_64"))]
+                VMArchType::X64 => return Err(wrong_host(&self.vm_arch_type)),
+                #[cfg(not(target_arch = "x86"))]
+                VMArchType::X32 => return Err(wrong_host(&self.vm_arch_type)),
+                #[cfg(not(target_arch = "aarch64"))]
+                VMArchType::AArch64 => return Err(wrong_host(&self.vm_arch_type)),
+            };
 
-            if ret.is_null() {
+            let result = if ret.is_null() {
                 Ok(())
             } else {
-                Err(*unsafe { Box::from_raw(ret) })
+                Err(self.locate(*unsafe { Box::from_raw(ret) }))
+            };
+            // the first error wins over a failure to flush
+            let flushed = self.flush_output().and_then(|_| self.output.flush());
+            return result.and(flushed);
+        }
+
+        /// Place `error` at the instruction which made the last call into
+        /// the callbacks. The x32 code doesn't record its calls.
+        fn locate(&self, error: bferror::error::RuntimeError) -> bferror::error::RuntimeError {
+            let start = self.code.ptr(AssemblyOffset(0)) as usize;
+            let offset = match self.fault.checked_sub(start) {
+                Some(offset) if self.fault != 0 => offset,
+                _ => return error,
+            };
+            match self.map.locate(offset) {
+                Some((index, sources)) => {
+                    let span = sources.of(&error.kind);
+                    return error.at(index, span);
+                }
+                None => return error,
             }
         }
 
         pub fn run(&mut self) -> Result<(), bferror::error::RuntimeError> {
             match self.vm_arch_type {
-                VMArchType::X64 => {
-                    return self.run_x64();
+                VMArchType::X64 if cfg!(target_arch = "x86_64") => {
+                    return self.run_raw();
                 }
-                _ => {
-                    return Err(bferror::error::RuntimeError {
-                        index: 1,
-                        kind: bferror::error::RuntimeErrorKind::Unknown,
-                    })
+                VMArchType::X32 if cfg!(target_arch = "x86") => {
+                    return self.run_raw();
+                }
+                VMArchType::AArch64 if cfg!(target_arch = "aarch64") => {
+                    return self.run_raw();
                 }
+                _ => return Err(wrong_host(&self.vm_arch_type)),
             }
         }
     }
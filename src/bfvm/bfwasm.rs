@@ -0,0 +1,127 @@
+pub mod wasm {
+    use std::io::{Read, Write};
+
+    use crate::bfparser::frontend::ir::Sources;
+    use crate::bftype::bferror;
+    use crate::bfvm::bfjit::vm;
+    use crate::bfvm::bfjit::vm::OutputBuffer;
+    use crate::Options;
+
+    /// Input and output of the program, reached from the imported functions.
+    struct WasmIO<'a> {
+        input: Box<dyn Read + 'a>,
+        output: OutputBuffer<'a>,
+    }
+
+    // exit status of a failed `read_byte`/`write_byte`, see `wasmgen::gen_wat`
+    const IO_STATUS: i32 = 1;
+
+    fn read_byte(mut caller: wasmi::Caller<'_, WasmIO<'_>>) -> Result<i32, wasmi::Error> {
+        let io = caller.data_mut();
+        if io.output.flush().is_err() {
+            return Err(wasmi::Error::i32_exit(IO_STATUS));
+        }
+        let mut buf = [0_u8];
+        match vm::read_input(&mut io.input, &mut buf) {
+            Ok(1) => Ok(buf[0] as i32),
+            Ok(0) => Ok(-1),
+            _ => Err(wasmi::Error::i32_exit(IO_STATUS)),
+        }
+    }
+
+    fn write_byte(
+        mut caller: wasmi::Caller<'_, WasmIO<'_>>,
+        byte: i32,
+    ) -> Result<(), wasmi::Error> {
+        match caller.data_mut().output.write_byte(byte as u8) {
+            Ok(()) => Ok(()),
+            Err(_) => Err(wasmi::Error::i32_exit(IO_STATUS)),
+        }
+    }
+
+    /// Runs a module of `wasmgen` in the embedded wasmi interpreter.
+    pub struct WasmStruct<'a> {
+        store: wasmi::Store<WasmIO<'a>>,
+        run: wasmi::TypedFunc<(), i32>,
+        /// Position of the failing instruction once `run` fails.
+        at: wasmi::Global,
+        /// Sources of each instruction, by position.
+        sources: &'a [Sources],
+    }
+
+    impl<'a> WasmStruct<'a> {
+        pub fn new(
+            code: &[u8],
+            sources: &'a [Sources],
+            input: Box<dyn Read + 'a>,
+            output: Box<dyn Write + 'a>,
+            options: &Options,
+        ) -> Result<Self, bferror::error::RuntimeError> {
+            let engine = wasmi::Engine::default();
+            let module = wasmi::Module::new(&engine, code).map_err(|_| Self::error(1))?;
+            let output = OutputBuffer::new(output, options.output_buffer);
+            let mut store = wasmi::Store::new(&engine, WasmIO { input, output });
+            let mut linker = wasmi::Linker::new(&engine);
+            linker
+                .func_wrap("env", "read_byte", read_byte)
+                .and_then(|linker| linker.func_wrap("env", "write_byte", write_byte))
+                .map_err(|_| Self::error(-1))?;
+            let instance = linker
+                .instantiate(&mut store, &module)
+                .and_then(|pre| pre.start(&mut store))
+                .map_err(|_| bferror::error::RuntimeError {
+                    index: 1,
+                    kind: bferror::error::RuntimeErrorKind::Memory,
+                    span: None,
+                })?;
+            let run = instance
+                .get_typed_func::<(), i32>(&store, "run")
+                .map_err(|_| Self::error(-1))?;
+            let at = instance
+                .get_global(&store, "at")
+                .ok_or_else(|| Self::error(-1))?;
+            Ok(Self {
+                store,
+                run,
+                at,
+                sources,
+            })
+        }
+
+        /// Turn an exit status of the module into an error.
+        fn error(status: i32) -> bferror::error::RuntimeError {
+            let kind = match status {
+                1 => bferror::error::RuntimeErrorKind::IO,
+                2 => bferror::error::RuntimeErrorKind::PointerOverflow,
+                3 => bferror::error::RuntimeErrorKind::CellOverflow,
+                4 => bferror::error::RuntimeErrorKind::PointerUnderflow,
+                _ => bferror::error::RuntimeErrorKind::Unknown,
+            };
+            bferror::error::RuntimeError {
+                index: 1,
+                kind,
+                span: None,
+            }
+        }
+
+        pub fn run(&mut self) -> Result<(), bferror::error::RuntimeError> {
+            let status = match self.run.call(&mut self.store, ()) {
+                Ok(status) => status,
+                Err(e) => e.i32_exit_status().unwrap_or(-1),
+            };
+            let flushed = self.store.data_mut().output.flush();
+            if status != 0 {
+                let error = Self::error(status);
+                let at = self.at.get(&self.store).i32().unwrap_or(-1);
+                return match self.sources.get(at as usize) {
+                    Some(sources) => {
+                        let span = sources.of(&error.kind);
+                        Err(error.at(at as usize, span))
+                    }
+                    None => Err(error),
+                };
+            }
+            return flushed;
+        }
+    }
+}
@@ -1 +1,3 @@
+pub mod bfinterp;
 pub mod bfjit;
+pub mod bfwasm;
@@ -0,0 +1,237 @@
+#![allow(
+    clippy::needless_return,
+    clippy::module_inception,
+    clippy::upper_case_acronyms,
+    clippy::ptr_arg
+)]
+
+pub mod bfparser;
+pub mod bftype;
+pub mod bfvm;
+
+use std::io::{Read, Write};
+
+use crate::bfparser::backend::codegen;
+use crate::bfparser::backend::{cgen, disasm, elf, llvmgen, wasmgen};
+use crate::bfparser::frontend::ir::{IRNode, Sources};
+use crate::bfparser::frontend::{ir, parser};
+use crate::bfparser::midend::optimizer;
+use crate::bftype::bfcate::bfcate::{VMArchType, VMBackendType, VMCellType, VMEmitType, VMEofType};
+use crate::bftype::bferror::error::{CompileError, RuntimeError};
+use crate::bfvm::bfinterp::interp::InterpStruct;
+use crate::bfvm::bfjit::vm;
+use crate::bfvm::bfjit::vm::VMStruct;
+use crate::bfvm::bfwasm::wasm::WasmStruct;
+
+/// Options used when compiling a program.
+#[derive(Clone, Debug)]
+pub struct Options {
+    /// Target architecture of the generated code.
+    pub vm_arch_type: VMArchType,
+    /// Backend executing the program, `Auto` falls back to the interpreter
+    /// when the JIT can't be used on this host.
+    pub backend: VMBackendType,
+    /// Number of cells on the tape.
+    pub tape_size: usize,
+    /// Width of a cell.
+    pub cell_type: VMCellType,
+    /// What `,` does at the end of the input.
+    pub eof_type: VMEofType,
+    /// Raise `CellOverflow` instead of wrapping cell arithmetic.
+    pub checked_cells: bool,
+    /// Grow the tape instead of failing when the pointer runs off either end.
+    pub growable: bool,
+    /// Read and write cells as ASCII digits ('0' is cell value 0).
+    pub add_48: bool,
+    /// Bytes of output held before writing them, 0 writes each byte
+    /// immediately. The buffer is also flushed on newlines, before reading
+    /// input and when the program stops. Unless it is 0, the x64 JIT also
+    /// batches `,` and `.` without calling back for every byte.
+    pub output_buffer: usize,
+    /// Bytes the x64 JIT reads ahead from the input for `,` when it batches
+    /// I/O. 0 reads one byte at a time, so the input is never consumed past
+    /// what the program reads.
+    pub input_buffer: usize,
+    /// Optimisation level from 0 to 3, selecting the passes to run.
+    pub opt_level: u8,
+    /// Passes run whatever the level.
+    pub passes: Vec<String>,
+    /// Passes never run, even if selected by the level or `passes`.
+    pub no_passes: Vec<String>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            vm_arch_type: VMArchType::default(),
+            backend: VMBackendType::default(),
+            tape_size: vm::MEMORY_SIZE,
+            cell_type: VMCellType::default(),
+            eof_type: VMEofType::default(),
+            checked_cells: false,
+            growable: false,
+            add_48: false,
+            output_buffer: vm::OUTPUT_BUFFER_SIZE,
+            input_buffer: 0,
+            opt_level: 3,
+            passes: vec![],
+            no_passes: vec![],
+        }
+    }
+}
+
+enum Code {
+    JIT(dynasmrt::ExecutableBuffer, codegen::CodeMap),
+    Interp(Vec<IRNode>),
+    /// The module and the sources of each instruction, by position.
+    Wasm(Vec<u8>, Vec<Sources>),
+}
+
+/// A compiled brainfuck program, ready to be run any number of times.
+pub struct Program {
+    code: Code,
+    options: Options,
+}
+
+/// Whether native code for `vm_arch_type` can be executed on this host.
+fn jit_supported(vm_arch_type: &VMArchType) -> bool {
+    match vm_arch_type {
+        VMArchType::X64 => cfg!(target_arch = "x86_64"),
+        VMArchType::X32 => cfg!(target_arch = "x86"),
+        VMArchType::AArch64 => cfg!(target_arch = "aarch64"),
+    }
+}
+
+fn gen_jit(irs: &Vec<IRNode>, options: &Options) -> Result<Code, CompileError> {
+    let mut map = codegen::CodeMap::default();
+    let code = codegen::gen_code_map(irs, options, &mut map).map_err(CompileError::internal)?;
+    Ok(Code::JIT(code, map))
+}
+
+fn gen_ir(src: &str, options: &Options) -> Result<Vec<IRNode>, CompileError> {
+    let tokens = parser::parse(src)?;
+    let irs = ir::transfer_to_ir(&tokens).map_err(CompileError::internal)?;
+    optimizer::optimize(irs, options)
+}
+
+/// Compile `src` into a standalone Linux executable for `options.vm_arch_type`.
+pub fn build_elf(src: &str, options: Options) -> Result<Vec<u8>, CompileError> {
+    let irs = gen_ir(src, &options)?;
+    match options.vm_arch_type {
+        VMArchType::X64 => codegen::gen_x64_elf(&irs, &options).map_err(CompileError::internal),
+        VMArchType::X32 => codegen::gen_x32_elf(&irs, &options).map_err(CompileError::internal),
+        VMArchType::AArch64 => {
+            codegen::gen_aarch64_elf(&irs, &options).map_err(CompileError::internal)
+        }
+    }
+}
+
+/// Print what the compiler produces for `src` instead of running it. The
+/// x32 and aarch64 code is the one of the standalone executable, with its
+/// addresses.
+pub fn emit(src: &str, options: Options, emit_type: VMEmitType) -> Result<Vec<u8>, CompileError> {
+    let text =
+        match emit_type {
+            VMEmitType::Tokens => parser::dump(&parser::parse(src)?),
+            VMEmitType::IR => ir::dump(&gen_ir(src, &options)?),
+            VMEmitType::Asm => {
+                let irs = gen_ir(src, &options)?;
+                let mut map = codegen::CodeMap::default();
+                match options.vm_arch_type {
+                    VMArchType::X64 => {
+                        let code = codegen::gen_code_map(&irs, &options, &mut map)
+                            .map_err(CompileError::internal)?;
+                        disasm::disassemble(&code, &VMArchType::X64, 0, &map.marks, &irs)
+                    }
+                    VMArchType::X32 => {
+                        let (code, _) = codegen::gen_x32_elf_code(&irs, &options, &mut map)
+                            .map_err(CompileError::internal)?;
+                        let ip = elf::ELF32_ENTRY_ADDR as u64;
+                        disasm::disassemble(&code, &VMArchType::X32, ip, &map.marks, &irs)
+                    }
+                    VMArchType::AArch64 => {
+                        let (code, _) = codegen::gen_aarch64_elf_code(&irs, &options, &mut map)
+                            .map_err(CompileError::internal)?;
+                        let ip = elf::ELF64_ENTRY_ADDR;
+                        disasm::disassemble(&code, &VMArchType::AArch64, ip, &map.marks, &irs)
+                    }
+                }
+            }
+            VMEmitType::C => cgen::gen_c(&gen_ir(src, &options)?, &options),
+            VMEmitType::Wat => wasmgen::gen_wat(&gen_ir(src, &options)?, &options)
+                .map_err(CompileError::internal)?,
+            VMEmitType::LLVM => llvmgen::gen_llvm(&gen_ir(src, &options)?, &options)
+                .map_err(CompileError::internal)?,
+            VMEmitType::Wasm => {
+                return wasmgen::gen_wasm(&gen_ir(src, &options)?, &options)
+                    .map_err(CompileError::internal)
+            }
+        };
+    Ok(text.into_bytes())
+}
+
+impl Program {
+    /// Parse, lower and compile `src` for the backend selected in `options`.
+    pub fn compile(src: &str, options: Options) -> Result<Program, CompileError> {
+        let irs = gen_ir(src, &options)?;
+        let code = match options.backend {
+            VMBackendType::JIT => gen_jit(&irs, &options)?,
+            VMBackendType::Interp => Code::Interp(irs),
+            VMBackendType::Wasm => {
+                let code = wasmgen::gen_wasm(&irs, &options).map_err(CompileError::internal)?;
+                Code::Wasm(code, ir::sources(&irs))
+            }
+            VMBackendType::Auto => {
+                if jit_supported(&options.vm_arch_type) {
+                    gen_jit(&irs, &options).unwrap_or(Code::Interp(irs))
+                } else {
+                    Code::Interp(irs)
+                }
+            }
+        };
+        Ok(Program { code, options })
+    }
+
+    /// The backend the program was compiled for.
+    pub fn backend(&self) -> VMBackendType {
+        match self.code {
+            Code::JIT(..) => VMBackendType::JIT,
+            Code::Interp(_) => VMBackendType::Interp,
+            Code::Wasm(..) => VMBackendType::Wasm,
+        }
+    }
+
+    /// Run the program, reading `,` from `input` and writing `.` to `output`.
+    ///
+    /// Reads are retried when interrupted. With `Options::input_buffer` set,
+    /// the x64 JIT may read up to that many bytes past the last one the
+    /// program consumes, and those bytes are lost to later readers of `input`.
+    pub fn run<'a>(
+        &self,
+        input: impl Read + 'a,
+        output: impl Write + 'a,
+    ) -> Result<(), RuntimeError> {
+        match &self.code {
+            Code::JIT(code, map) => {
+                let mut vm =
+                    VMStruct::new(code, map, Box::new(input), Box::new(output), &self.options)?;
+                vm.run()
+            }
+            Code::Interp(irs) => {
+                let mut interp =
+                    InterpStruct::new(irs, Box::new(input), Box::new(output), &self.options)?;
+                interp.run()
+            }
+            Code::Wasm(code, sources) => {
+                let mut wasm = WasmStruct::new(
+                    code,
+                    sources,
+                    Box::new(input),
+                    Box::new(output),
+                    &self.options,
+                )?;
+                wasm.run()
+            }
+        }
+    }
+}
@@ -1,13 +1,15 @@
-mod bfparser;
-mod bftype;
-mod bfvm;
+#![allow(clippy::needless_return)]
+
+use std::process::ExitCode;
+
 mod start;
 
-fn main() {
-    let args = start::parse();
-    if args.is_err() {
-        println!("parse args error");
-        return;
+fn main() -> ExitCode {
+    match start::parse().and_then(start::start_all) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            e.report();
+            e.exit_code()
+        }
     }
-    start::start_all(args.unwrap());
 }
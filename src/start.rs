@@ -1,108 +1,399 @@
-use clap::Parser;
-use std::{fs::File, io::Read, io::Write, path::PathBuf};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use std::process::ExitCode;
+use std::{fmt, fs::File, io::Read, io::Write, path::PathBuf};
 
-use crate::bftype::bfcate::bfcate::VMArchType;
-use crate::bftype::bferror;
-use crate::bftype::bfwarn;
+use bfjit::bfparser::midend::optimizer;
+use bfjit::bftype::bfcate::bfcate::{VMArchType, VMBackendType, VMCellType, VMEmitType, VMEofType};
+use bfjit::bftype::bferror;
+use bfjit::bfvm::bfjit::vm;
+use bfjit::{Options, Program};
 
 const STDIN: &str = "STDIN";
 const STDOUT: &str = "STDOUT";
 
+/// Exit status when the program fails while running.
+const EXIT_RUNTIME: u8 = 1;
+/// Exit status of invalid arguments, the one clap uses.
+const EXIT_USAGE: u8 = 2;
+/// Exit status when the program doesn't compile.
+const EXIT_COMPILE: u8 = 3;
+/// Exit status when reading or writing a file or stream fails.
+const EXIT_IO: u8 = 4;
+
+/// Why a command failed, reported on stderr.
+pub enum Failure {
+    Usage(clap::Error),
+    /// The rendered diagnostics.
+    Compile(String),
+    /// The error and its rendered diagnostic.
+    Runtime(bferror::error::RuntimeError, String),
+    /// What failed and why.
+    IO(String, std::io::Error),
+}
+
+impl Failure {
+    pub fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Failure::Usage(_) => EXIT_USAGE,
+            Failure::Compile(_) => EXIT_COMPILE,
+            Failure::Runtime(e, _) => match e.kind {
+                bferror::error::RuntimeErrorKind::IO => EXIT_IO,
+                _ => EXIT_RUNTIME,
+            },
+            Failure::IO(..) => EXIT_IO,
+        })
+    }
+
+    /// Print the failure on stderr.
+    pub fn report(&self) {
+        match self {
+            // clap formats its own errors, with the usage
+            Failure::Usage(e) => {
+                let _ = e.print();
+            }
+            Failure::Compile(diagnostics) | Failure::Runtime(_, diagnostics) => {
+                eprint!("{}", diagnostics)
+            }
+            _ => eprintln!("error: {}", self),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Failure::Usage(e) => write!(f, "{}", e),
+            Failure::Compile(e) => write!(f, "{}", e),
+            Failure::Runtime(e, _) => write!(f, "{}", e),
+            Failure::IO(what, e) => write!(f, "{}: {}", what, e),
+        }
+    }
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum Backend {
+    Auto,
+    Jit,
+    Interp,
+    Wasm,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum Arch {
+    X64,
+    X32,
+    #[clap(name = "aarch64")]
+    AArch64,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum CellSize {
+    #[clap(name = "8")]
+    U8,
+    #[clap(name = "16")]
+    U16,
+    #[clap(name = "32")]
+    U32,
+    #[clap(name = "64")]
+    U64,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum Eof {
+    Error,
+    Zero,
+    MinusOne,
+    Unchanged,
+}
+
+#[derive(Clone, Debug, ValueEnum)]
+enum Emit {
+    Tokens,
+    Ir,
+    Asm,
+    C,
+    Wat,
+    Wasm,
+    Llvm,
+}
+
 #[derive(Debug, Parser)]
-#[clap(version)]
+#[clap(
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true,
+    after_help = "Exit status: 0 on success, 1 if the program fails while running,\n\
+                  2 on invalid arguments, 3 if the program doesn't compile and 4 on\n\
+                  input or output errors."
+)]
 struct Opt {
-    #[clap(name = "FILE")]
-    file_path: PathBuf,
+    #[clap(subcommand)]
+    command: Option<Command>,
+    #[clap(name = "FILE", required = true)]
+    file_path: Option<PathBuf>,
     #[clap(short='i', long="input", help="input file or STDIN", default_value_t = String::from(STDIN))]
     input: String,
     #[clap(short='o', long="output", help="output file or STDOUT", default_value_t = String::from(STDOUT))]
     output: String,
+    #[clap(short='b', long="backend", help="backend executing the program", value_enum, default_value_t = Backend::Auto)]
+    backend: Backend,
+    #[clap(
+        long = "emit",
+        help = "print the compiler output instead of running",
+        value_enum
+    )]
+    emit: Option<Emit>,
+    #[clap(
+        long = "unbuffered",
+        help = "write every output byte immediately and read input a byte at a time, for interactive programs"
+    )]
+    unbuffered: bool,
+    #[clap(flatten)]
+    common: CommonOpt,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Compile FILE into a standalone static Linux executable
+    Build(BuildOpt),
+}
+
+#[derive(Debug, Args)]
+struct BuildOpt {
+    #[clap(name = "FILE")]
+    file_path: PathBuf,
+    #[clap(short = 'o', long = "output", help = "path of the executable")]
+    output: PathBuf,
+    #[clap(flatten)]
+    common: CommonOpt,
+}
+
+// options shared by running and building
+#[derive(Debug, Args)]
+struct CommonOpt {
+    #[clap(short='a', long="arch", help="target architecture", value_enum, default_value_t = Arch::X64)]
+    arch: Arch,
+    #[clap(short='t', long="tape-size", help="number of cells on the tape", value_parser = clap::value_parser!(u64).range(1..), default_value_t = vm::MEMORY_SIZE as u64)]
+    tape_size: u64,
+    #[clap(short='c', long="cell-size", help="width of a cell in bits", value_enum, default_value_t = CellSize::U8)]
+    cell_size: CellSize,
+    #[clap(short='e', long="eof", help="what `,` does at the end of the input", value_enum, default_value_t = Eof::Error)]
+    eof: Eof,
+    #[clap(
+        long = "checked-cells",
+        help = "raise an error on cell overflow instead of wrapping"
+    )]
+    checked_cells: bool,
+    #[clap(
+        short = 'g',
+        long = "growable",
+        help = "grow the tape when the pointer runs off either end"
+    )]
+    growable: bool,
+    #[clap(short='O', help="optimisation level", value_parser = clap::value_parser!(u8).range(0..=3), default_value_t = 3)]
+    opt_level: u8,
+    #[clap(long="pass", help="run an optimisation pass whatever the level", value_parser = pass_names())]
+    passes: Vec<String>,
+    #[clap(long="no-pass", help="never run an optimisation pass", value_parser = pass_names())]
+    no_passes: Vec<String>,
+}
+
+fn pass_names() -> clap::builder::PossibleValuesParser {
+    let names: Vec<&'static str> = optimizer::all_passes()
+        .iter()
+        .map(|pass| pass.name())
+        .collect();
+    clap::builder::PossibleValuesParser::new(names)
 }
 
 pub struct StartArgs {
     vm_arch_type: VMArchType,
+    build: Option<PathBuf>,
+    emit: Option<VMEmitType>,
+    backend: VMBackendType,
+    tape_size: usize,
+    cell_type: VMCellType,
+    eof_type: VMEofType,
+    checked_cells: bool,
+    growable: bool,
+    output_buffer: usize,
+    input_buffer: usize,
+    opt_level: u8,
+    passes: Vec<String>,
+    no_passes: Vec<String>,
     input: Box<dyn Read>,
     output: Box<dyn Write>,
-    str: String,
+    file_path: PathBuf,
 }
 
-pub fn start_all(args: StartArgs) {
-    let tokens_res = crate::bfparser::frontend::parser::parse(args.str.as_str());
-    if tokens_res.is_err() {
-        println!("{:?}", tokens_res.as_ref().unwrap_err());
-        return;
-    }
-    let irs_res = crate::bfparser::frontend::ir::transfer_to_ir(&tokens_res.unwrap());
-    if irs_res.is_err() {
-        println!("{:?}", irs_res.as_ref().unwrap_err());
-        return;
+pub fn start_all(args: StartArgs) -> Result<(), Failure> {
+    let src = read_source(&args.file_path)?;
+    let name = args.file_path.display().to_string();
+    let compile_failure = |e: bferror::error::CompileError| Failure::Compile(e.render(&src, &name));
+    let options = Options {
+        vm_arch_type: args.vm_arch_type,
+        backend: args.backend,
+        tape_size: args.tape_size,
+        cell_type: args.cell_type,
+        eof_type: args.eof_type,
+        checked_cells: args.checked_cells,
+        growable: args.growable,
+        output_buffer: args.output_buffer,
+        input_buffer: args.input_buffer,
+        opt_level: args.opt_level,
+        passes: args.passes,
+        no_passes: args.no_passes,
+        ..Default::default()
+    };
+    if let Some(emit_type) = args.emit {
+        let bytes = bfjit::emit(&src, options, emit_type).map_err(compile_failure)?;
+        let mut output = args.output;
+        return output
+            .write_all(&bytes)
+            .and_then(|_| output.flush())
+            .map_err(|e| Failure::IO(String::from("cannot write the output"), e));
     }
-    let asm_res =
-        crate::bfparser::backend::codegen::gen_code(&irs_res.unwrap(), args.vm_arch_type.clone());
-    if asm_res.is_err() {
-        println!("{:?}", asm_res.as_ref().unwrap_err());
-        return;
+    if let Some(path) = args.build {
+        let elf = bfjit::build_elf(&src, options).map_err(compile_failure)?;
+        return write_executable(&path, &elf)
+            .map_err(|e| Failure::IO(format!("cannot write `{}`", path.display()), e));
     }
-    let vm_res = crate::bfvm::bfjit::vm::VMStruct::new(
-        asm_res.unwrap(),
-        args.input,
-        args.output,
-        args.vm_arch_type.clone(),
-        false,
-    );
-    if vm_res.is_err() {
-        println!("{:?}", "VMStruct::new error");
-        return;
-    }
-    let tot_res = vm_res.unwrap().run();
-    if tot_res.is_err() {
-        println!("{:?}", tot_res.as_ref().unwrap_err());
-        return;
+    let program = Program::compile(&src, options).map_err(compile_failure)?;
+    return program.run(args.input, args.output).map_err(|e| {
+        let diagnostic = e.render(&src, &name);
+        Failure::Runtime(e, diagnostic)
+    });
+}
+
+fn write_executable(path: &PathBuf, bytes: &[u8]) -> std::io::Result<()> {
+    std::fs::write(path, bytes)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
     }
+    Ok(())
 }
 
-pub fn parse() -> Result<StartArgs, bferror::error::RuntimeError> {
-    let opt = Opt::parse();
-    let src = std::fs::read_to_string(opt.file_path);
-    if src.is_err() {
-        return Err(bferror::error::RuntimeError {
-            index: 1,
-            kind: bferror::error::RuntimeErrorKind::IO,
-        });
-    } else {
-        let mut input: Box<dyn Read> = Box::new(std::io::stdin());
-        let mut output: Box<dyn Write> = Box::new(std::io::stdout());
-        if opt.input != STDIN {
-            let input_res = File::open(opt.input);
-            if input_res.is_err() {
-                println!(
-                    "{}",
-                    bfwarn::warn::RuntimeWarn {
-                        kind: bfwarn::warn::RuntimeWarnKind::ParseInputWarn,
-                    }
-                )
-            } else {
-                input = Box::new(input_res.unwrap());
-            }
-        }
-        if opt.output != STDOUT {
-            let output_res = File::create(opt.output);
-            if output_res.is_err() {
-                println!(
-                    "{}",
-                    bfwarn::warn::RuntimeWarn {
-                        kind: bfwarn::warn::RuntimeWarnKind::ParseOutputWarn,
-                    }
-                )
-            } else {
-                output = Box::new(output_res.unwrap());
-            }
-        }
-        return Ok(StartArgs {
-            vm_arch_type: VMArchType::X64,
+fn read_source(path: &PathBuf) -> Result<String, Failure> {
+    return std::fs::read_to_string(path)
+        .map_err(|e| Failure::IO(format!("cannot read `{}`", path.display()), e));
+}
+
+fn common_args(
+    common: CommonOpt,
+    build: Option<PathBuf>,
+    emit: Option<VMEmitType>,
+    backend: VMBackendType,
+    input: Box<dyn Read>,
+    output: Box<dyn Write>,
+    file_path: PathBuf,
+) -> StartArgs {
+    return StartArgs {
+        vm_arch_type: match common.arch {
+            Arch::X64 => VMArchType::X64,
+            Arch::X32 => VMArchType::X32,
+            Arch::AArch64 => VMArchType::AArch64,
+        },
+        build,
+        emit,
+        tape_size: common.tape_size as usize,
+        cell_type: match common.cell_size {
+            CellSize::U8 => VMCellType::U8,
+            CellSize::U16 => VMCellType::U16,
+            CellSize::U32 => VMCellType::U32,
+            CellSize::U64 => VMCellType::U64,
+        },
+        eof_type: match common.eof {
+            Eof::Error => VMEofType::Error,
+            Eof::Zero => VMEofType::Zero,
+            Eof::MinusOne => VMEofType::MinusOne,
+            Eof::Unchanged => VMEofType::Unchanged,
+        },
+        checked_cells: common.checked_cells,
+        growable: common.growable,
+        output_buffer: vm::OUTPUT_BUFFER_SIZE,
+        input_buffer: vm::INPUT_BUFFER_SIZE,
+        opt_level: common.opt_level,
+        passes: common.passes,
+        no_passes: common.no_passes,
+        backend,
+        input,
+        output,
+        file_path,
+    };
+}
+
+pub fn parse() -> Result<StartArgs, Failure> {
+    let opt = match Opt::try_parse() {
+        Ok(opt) => opt,
+        // --help and --version print to stdout and exit successfully
+        Err(e) if !e.use_stderr() => e.exit(),
+        Err(e) => return Err(Failure::Usage(e)),
+    };
+    if let Some(Command::Build(build)) = opt.command {
+        return Ok(common_args(
+            build.common,
+            Some(build.output),
+            None,
+            VMBackendType::JIT,
+            Box::new(std::io::stdin()),
+            Box::new(std::io::stdout()),
+            build.file_path,
+        ));
+    }
+    if matches!(opt.backend, Backend::Wasm) && opt.common.growable {
+        return Err(Failure::Usage(Opt::command().error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "`--growable` is not supported by the wasm backend",
+        )));
+    }
+    let mut input: Box<dyn Read> = Box::new(std::io::stdin());
+    let mut output: Box<dyn Write> = Box::new(std::io::stdout());
+    if opt.input != STDIN {
+        let file = File::open(&opt.input)
+            .map_err(|e| Failure::IO(format!("cannot open `{}`", opt.input), e))?;
+        input = Box::new(file);
+    }
+    if opt.output != STDOUT {
+        let file = File::create(&opt.output)
+            .map_err(|e| Failure::IO(format!("cannot create `{}`", opt.output), e))?;
+        output = Box::new(file);
+    }
+    let emit = opt.emit.map(|emit| match emit {
+        Emit::Tokens => VMEmitType::Tokens,
+        Emit::Ir => VMEmitType::IR,
+        Emit::Asm => VMEmitType::Asm,
+        Emit::C => VMEmitType::C,
+        Emit::Wat => VMEmitType::Wat,
+        Emit::Wasm => VMEmitType::Wasm,
+        Emit::Llvm => VMEmitType::LLVM,
+    });
+    let backend = match opt.backend {
+        Backend::Auto => VMBackendType::Auto,
+        Backend::Jit => VMBackendType::JIT,
+        Backend::Interp => VMBackendType::Interp,
+        Backend::Wasm => VMBackendType::Wasm,
+    };
+    return Ok(StartArgs {
+        output_buffer: if opt.unbuffered {
+            0
+        } else {
+            vm::OUTPUT_BUFFER_SIZE
+        },
+        input_buffer: if opt.unbuffered {
+            0
+        } else {
+            vm::INPUT_BUFFER_SIZE
+        },
+        ..common_args(
+            opt.common,
+            None,
+            emit,
+            backend,
             input,
             output,
-            str: src.unwrap(),
-        });
-    }
+            opt.file_path.unwrap_or_default(),
+        )
+    });
 }
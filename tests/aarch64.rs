@@ -0,0 +1,75 @@
+//! The aarch64 backend can only run on aarch64 hosts, so its output is
+//! compared against a known good disassembly, and the executable is run
+//! under `qemu-aarch64` when it is installed.
+
+#![allow(clippy::needless_return)]
+
+use std::process::Command;
+
+use bfjit::bftype::bfcate::bfcate::{VMArchType, VMBackendType, VMEmitType};
+use bfjit::{Options, Program};
+
+// prints "AB" then copies one byte of input
+const SRC: &str = "++++++++[>++++++++<-]>+.+.,.";
+
+fn options() -> Options {
+    Options {
+        vm_arch_type: VMArchType::AArch64,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn asm_matches_golden() {
+    let asm = bfjit::emit(SRC, options(), VMEmitType::Asm).unwrap();
+    let golden = include_str!("golden/aarch64.s");
+    // regenerate with `bfjit --emit asm -a aarch64` on `SRC`
+    assert_eq!(String::from_utf8(asm).unwrap(), golden);
+}
+
+#[test]
+fn jit_on_other_hosts() {
+    if cfg!(target_arch = "aarch64") {
+        return;
+    }
+    let options = Options {
+        backend: VMBackendType::JIT,
+        ..options()
+    };
+    let error = Program::compile(SRC, options).err().unwrap();
+    assert_eq!(error.to_string(), "the aarch64 JIT needs an aarch64 host");
+}
+
+#[test]
+fn elf_header() {
+    let elf = bfjit::build_elf(SRC, options()).unwrap();
+    assert_eq!(&elf[..5], b"\x7fELF\x02");
+    // e_machine is EM_AARCH64
+    assert_eq!(u16::from_le_bytes([elf[18], elf[19]]), 183);
+}
+
+#[test]
+fn elf_runs_under_qemu() {
+    if Command::new("qemu-aarch64")
+        .arg("--version")
+        .output()
+        .is_err()
+    {
+        eprintln!("qemu-aarch64 not found, skipped");
+        return;
+    }
+    let path = std::env::temp_dir().join(format!("bfjit-aarch64-{}", std::process::id()));
+    std::fs::write(&path, bfjit::build_elf(SRC, options()).unwrap()).unwrap();
+    let mut child = Command::new("qemu-aarch64")
+        .arg(&path)
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    use std::io::Write;
+    child.stdin.take().unwrap().write_all(b"C").unwrap();
+    let output = child.wait_with_output().unwrap();
+    let _ = std::fs::remove_file(&path);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"ABC");
+}
@@ -0,0 +1,154 @@
+//! Every backend, `-O` level and cell width must run a program the way the
+//! interpreter does without optimisations.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use bfjit::bftype::bfcate::bfcate::{VMBackendType, VMCellType, VMEofType};
+use bfjit::bfvm::bfjit::vm;
+use bfjit::{Options, Program};
+
+const HELLO: &str = "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.>>.<-.<.+++.------.--------.>>+.>++.";
+
+/// A program, its input and the options it needs beside those compared.
+struct Case {
+    src: &'static str,
+    input: &'static [u8],
+    options: Options,
+}
+
+fn case(src: &'static str, input: &'static [u8]) -> Case {
+    return Case {
+        src,
+        input,
+        options: Options::default(),
+    };
+}
+
+fn corpus() -> Vec<Case> {
+    let mut cases = vec![
+        case(HELLO, b""),
+        // 256 is 0 only in a byte
+        case("++++++++[>++++++++<-]>[<++++>-]<[[-]>+<]>.", b""),
+        // copy, multiply and clear loops
+        case("+++++[>+>++>+++<<<-]>.>.>.[-]<[->>+<<]>>.", b""),
+        // scans both ways
+        case("+>+>+>+>>+<<[<]>.>>>>[>]<.", b""),
+        case(",[.,]", b"echo\n"),
+        case("+++++[>,.<-]", b"abc"),
+        // the pointer leaves the tape after printing
+        case("+.<", b""),
+    ];
+    for eof_type in [
+        VMEofType::Zero,
+        VMEofType::MinusOne,
+        VMEofType::Unchanged,
+        VMEofType::Error,
+    ] {
+        cases.push(Case {
+            options: Options {
+                eof_type,
+                ..Default::default()
+            },
+            ..case(",>,>+++,<<.>.>.", b"ab")
+        });
+    }
+    cases.push(Case {
+        options: Options {
+            tape_size: 16,
+            ..Default::default()
+        },
+        ..case("+[>+.]", b"")
+    });
+    cases.push(Case {
+        options: Options {
+            checked_cells: true,
+            ..Default::default()
+        },
+        ..case("++.>+[->-]", b"")
+    });
+    return cases;
+}
+
+/// `(output_buffer, input_buffer)` pairs, the x64 JIT batching `,` and `.`
+/// only with an output buffer.
+const BUFFERS: [(usize, usize); 3] = [
+    (0, 0),
+    (vm::OUTPUT_BUFFER_SIZE, 0),
+    (vm::OUTPUT_BUFFER_SIZE, vm::INPUT_BUFFER_SIZE),
+];
+
+/// The output of `case` and the kind of its error, if any.
+fn run(case: &Case, options: Options) -> (Vec<u8>, Option<String>) {
+    let program = Program::compile(case.src, options).unwrap();
+    let mut output = vec![];
+    let result = program.run(case.input, &mut output);
+    return (output, result.err().map(|e| format!("{:?}", e.kind)));
+}
+
+#[test]
+fn backends_agree() {
+    for case in corpus() {
+        for cell_type in [
+            VMCellType::U8,
+            VMCellType::U16,
+            VMCellType::U32,
+            VMCellType::U64,
+        ] {
+            let reference = run(
+                &case,
+                Options {
+                    backend: VMBackendType::Interp,
+                    cell_type,
+                    opt_level: 0,
+                    ..case.options.clone()
+                },
+            );
+            for backend in common::backends() {
+                for opt_level in 0..=3 {
+                    for (output_buffer, input_buffer) in BUFFERS {
+                        let options = Options {
+                            backend: backend.clone(),
+                            cell_type,
+                            opt_level,
+                            output_buffer,
+                            input_buffer,
+                            ..case.options.clone()
+                        };
+                        assert_eq!(
+                            run(&case, options),
+                            reference,
+                            "{:?} -O{} {:?} buffers {:?} on {:?}",
+                            backend,
+                            opt_level,
+                            cell_type,
+                            (output_buffer, input_buffer),
+                            case.src
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn corpus_outputs() {
+    let output = |case: &Case, cell_type| {
+        let options = Options {
+            cell_type,
+            ..case.options.clone()
+        };
+        return run(case, options);
+    };
+    let cases = corpus();
+    assert_eq!(output(&cases[0], VMCellType::U8).0, b"Hello World!\n");
+    assert_eq!(output(&cases[1], VMCellType::U8).0, [0]);
+    assert_eq!(output(&cases[1], VMCellType::U16).0, [1]);
+    assert_eq!(output(&cases[4], VMCellType::U8).0, b"echo\n");
+    assert_eq!(
+        output(&cases[6], VMCellType::U8),
+        (vec![1], Some(String::from("PointerUnderflow")))
+    );
+}
@@ -0,0 +1,107 @@
+//! The C output, built with the undefined behaviour sanitizer, must run a
+//! program the way the interpreter does. Skipped without a C compiler.
+
+#![allow(clippy::needless_return)]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use bfjit::bftype::bfcate::bfcate::{VMBackendType, VMCellType, VMEmitType, VMEofType};
+use bfjit::{Options, Program};
+
+/// Programs and their input, with multiplications by negative factors of
+/// the largest cell values.
+const CASES: [(&str, &[u8]); 4] = [
+    (",-[->-<]>.", b"\0"),
+    (",-[->+++>---<<]>.>.", b"\0"),
+    (",[->--->+++<<]>.>.", b"\xff"),
+    (
+        "++++++++[>++++[>++>+++>+++>+<<<<-]>+>+>->>+[<]<-]>>.>---.+++++++..+++.",
+        b"",
+    ),
+];
+
+fn have_cc() -> bool {
+    return Command::new("cc")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .status()
+        .is_ok();
+}
+
+/// Build the C output of `src` with `-fsanitize=undefined` and run it.
+fn run_c(name: &str, src: &str, input: &[u8], options: Options) -> (Vec<u8>, i32) {
+    let c = bfjit::emit(src, options, VMEmitType::C).unwrap();
+    let dir = std::env::temp_dir();
+    let base = format!("bfjit-cgen-{}-{}", name, std::process::id());
+    let source = dir.join(format!("{}.c", base));
+    let exe = dir.join(base);
+    std::fs::write(&source, c).unwrap();
+    let status = Command::new("cc")
+        .args([
+            "-fsanitize=undefined",
+            "-fno-sanitize-recover=undefined",
+            "-o",
+        ])
+        .arg(&exe)
+        .arg(&source)
+        .status()
+        .unwrap();
+    assert!(status.success(), "cc failed on {:?}", src);
+    let mut child = Command::new(&exe)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&source).unwrap();
+    std::fs::remove_file(&exe).unwrap();
+    assert!(
+        output.stderr.is_empty(),
+        "{:?}: {}",
+        src,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    return (output.stdout, output.status.code().unwrap());
+}
+
+#[test]
+fn sanitized_c_matches_interp() {
+    if !have_cc() {
+        return;
+    }
+    for (index, (src, input)) in CASES.iter().enumerate() {
+        for cell_type in [
+            VMCellType::U8,
+            VMCellType::U16,
+            VMCellType::U32,
+            VMCellType::U64,
+        ] {
+            let options = Options {
+                cell_type,
+                eof_type: VMEofType::Zero,
+                ..Default::default()
+            };
+            let program = Program::compile(
+                src,
+                Options {
+                    backend: VMBackendType::Interp,
+                    ..options.clone()
+                },
+            )
+            .unwrap();
+            let mut expected = vec![];
+            program.run(*input, &mut expected).unwrap();
+            let name = format!("{}-{:?}", index, cell_type);
+            assert_eq!(
+                run_c(&name, src, input, options),
+                (expected, 0),
+                "{:?} on {:?}",
+                cell_type,
+                src
+            );
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! `--checked-cells` must report the first overflow whatever the backend
+//! and the optimisation level.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use bfjit::bftype::bferror::error::RuntimeErrorKind;
+use bfjit::{Options, Program};
+
+/// Whether running `src` on `input` overflows a cell, for every backend
+/// and `-O` level, which must all agree.
+fn overflows(src: &str, input: &[u8]) -> bool {
+    let mut results = vec![];
+    for backend in common::backends() {
+        for opt_level in 0..=3 {
+            let options = Options {
+                backend: backend.clone(),
+                checked_cells: true,
+                opt_level,
+                ..Default::default()
+            };
+            let program = Program::compile(src, options).unwrap();
+            let result = match program.run(input, vec![]) {
+                Ok(()) => false,
+                Err(e) if matches!(e.kind, RuntimeErrorKind::CellOverflow) => true,
+                Err(e) => panic!("{:?} -O{}: {}", backend, opt_level, e),
+            };
+            results.push((backend.clone(), opt_level, result));
+        }
+    }
+    let first = results[0].2;
+    for (backend, opt_level, result) in &results {
+        assert_eq!(*result, first, "{:?} -O{} on {:?}", backend, opt_level, src);
+    }
+    return first;
+}
+
+#[test]
+fn opposite_changes_on_zero_cell() {
+    assert!(overflows("-+", b""));
+    assert!(overflows(">>>>>>>>-+", b""));
+    assert!(!overflows("+-", b""));
+}
+
+#[test]
+fn opposite_changes_on_max_cell() {
+    assert!(overflows(",+-", b"\xff"));
+    assert!(overflows(",>,<+->+-", b"\x00\xff"));
+    assert!(!overflows(",-+", b"\xff"));
+}
+
+#[test]
+fn same_changes() {
+    assert!(overflows(",++", b"\xfe"));
+    assert!(!overflows(",+", b"\xfe"));
+    assert!(overflows(",--", b"\x01"));
+}
@@ -0,0 +1,87 @@
+//! Exit statuses of the command line.
+
+#![allow(clippy::needless_return)]
+
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const EXIT_USAGE: i32 = 2;
+const EXIT_COMPILE: i32 = 3;
+const EXIT_IO: i32 = 4;
+
+/// A program of its own for each test, as they run in parallel.
+fn source(test: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("bfjit-{}-{}.bf", test, std::process::id()));
+    std::fs::write(&path, "+.").unwrap();
+    return path;
+}
+
+fn run(test: &str, args: &[&str]) -> Output {
+    let path = source(test);
+    let output = Command::new(env!("CARGO_BIN_EXE_bfjit"))
+        .args(args)
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    return output;
+}
+
+fn status(test: &str, args: &[&str]) -> i32 {
+    return run(test, args).status.code().unwrap();
+}
+
+#[test]
+fn missing_input_file() {
+    assert_eq!(
+        status("missing_input_file", &["-i", "/nonexistent/input"]),
+        EXIT_IO
+    );
+}
+
+#[test]
+fn uncreatable_output_file() {
+    assert_eq!(
+        status("uncreatable_output_file", &["-o", "/nonexistent/output"]),
+        EXIT_IO
+    );
+}
+
+#[test]
+fn empty_tape() {
+    assert_eq!(status("empty_tape", &["--tape-size", "0"]), EXIT_USAGE);
+}
+
+#[test]
+fn growable_wasm() {
+    assert_eq!(
+        status("growable_wasm", &["--backend", "wasm", "-g"]),
+        EXIT_USAGE
+    );
+}
+
+#[test]
+fn llvm_tape_too_large() {
+    let output = run(
+        "llvm_tape_too_large",
+        &["--emit", "llvm", "-t", "4611686018427387904", "-c", "64"],
+    );
+    assert_eq!(output.status.code(), Some(EXIT_COMPILE));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: tape is too large for the llvm output\n"
+    );
+}
+
+#[test]
+fn x32_jit_on_other_hosts() {
+    if cfg!(target_arch = "x86") {
+        return;
+    }
+    let output = run("x32_jit_on_other_hosts", &["-b", "jit", "-a", "x32"]);
+    assert_eq!(output.status.code(), Some(EXIT_COMPILE));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "error: the x32 JIT needs an x86 host\n"
+    );
+}
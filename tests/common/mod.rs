@@ -0,0 +1,14 @@
+//! Helpers shared by the integration tests.
+
+#![allow(clippy::needless_return)]
+
+use bfjit::bftype::bfcate::bfcate::VMBackendType;
+
+/// The backends able to run on this host.
+pub fn backends() -> Vec<VMBackendType> {
+    let mut backends = vec![VMBackendType::Interp, VMBackendType::Wasm];
+    if cfg!(target_arch = "x86_64") {
+        backends.push(VMBackendType::JIT);
+    }
+    return backends;
+}
@@ -0,0 +1,144 @@
+//! The standalone executables print what the program prints, exit with 2
+//! when the pointer leaves the tape and with 3 on a checked cell overflow.
+//! They are run on Linux x86 hosts.
+
+#![allow(clippy::needless_return)]
+
+use std::io::Write;
+use std::os::unix::fs::PermissionsExt;
+use std::process::{Command, Stdio};
+
+use bfjit::bftype::bfcate::bfcate::{VMArchType, VMEmitType};
+use bfjit::Options;
+
+// prints "AB" then copies one byte of input
+const SRC: &str = "++++++++[>++++++++<-]>+.+.,.";
+
+const EXIT_TAPE: i32 = 2;
+const EXIT_CELL: i32 = 3;
+
+// errno of `exec` when the file is still open for writing by a child forked
+// by another test, and when the kernel can't run 32-bit executables
+const ETXTBSY: i32 = 26;
+const ENOEXEC: i32 = 8;
+
+fn can_run(arch: &VMArchType) -> bool {
+    return cfg!(target_os = "linux")
+        && match arch {
+            VMArchType::X64 => cfg!(target_arch = "x86_64"),
+            VMArchType::X32 => cfg!(any(target_arch = "x86", target_arch = "x86_64")),
+            VMArchType::AArch64 => false,
+        };
+}
+
+/// The output and exit status of the executable of `src`, or `None` when
+/// the host can't run it.
+fn run_elf(name: &str, src: &str, input: &[u8], options: Options) -> Option<(Vec<u8>, i32)> {
+    if !can_run(&options.vm_arch_type) {
+        return None;
+    }
+    let elf = bfjit::build_elf(src, options).unwrap();
+    let path = std::env::temp_dir().join(format!("bfjit-elf-{}-{}", name, std::process::id()));
+    std::fs::write(&path, elf).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let spawned = loop {
+        let spawned = Command::new(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn();
+        match spawned {
+            Err(e) if e.raw_os_error() == Some(ETXTBSY) => continue,
+            spawned => break spawned,
+        }
+    };
+    let mut child = match spawned {
+        Ok(child) => child,
+        Err(e) if e.raw_os_error() == Some(ENOEXEC) => {
+            std::fs::remove_file(&path).unwrap();
+            return None;
+        }
+        Err(e) => panic!("{}: {}", name, e),
+    };
+    child.stdin.take().unwrap().write_all(input).unwrap();
+    let output = child.wait_with_output().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    return Some((output.stdout, output.status.code().unwrap()));
+}
+
+fn x32() -> Options {
+    Options {
+        vm_arch_type: VMArchType::X32,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn x32_asm_matches_golden() {
+    let asm = bfjit::emit(SRC, x32(), VMEmitType::Asm).unwrap();
+    let golden = include_str!("golden/x32.s");
+    // regenerate with `bfjit --emit asm -a x32` on `SRC`
+    assert_eq!(String::from_utf8(asm).unwrap(), golden);
+}
+
+#[test]
+fn x32_elf_runs() {
+    if let Some(result) = run_elf("x32_elf_runs", SRC, b"x", x32()) {
+        assert_eq!(result, (b"ABx".to_vec(), 0));
+    }
+}
+
+#[test]
+fn x32_elf_leaves_the_tape() {
+    let options = Options {
+        tape_size: 4,
+        ..x32()
+    };
+    // on each side, after printing what it printed before
+    let cases: [(&str, &[u8]); 2] = [("+.<", &[1]), ("+.[>+]", &[1])];
+    for (index, (src, printed)) in cases.iter().enumerate() {
+        let name = format!("x32_elf_leaves_the_tape-{}", index);
+        if let Some(result) = run_elf(&name, src, b"", options.clone()) {
+            assert_eq!(result, (printed.to_vec(), EXIT_TAPE), "{:?}", src);
+        }
+    }
+}
+
+fn x64() -> Options {
+    Options {
+        vm_arch_type: VMArchType::X64,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn x64_elf_runs() {
+    if let Some(result) = run_elf("x64_elf_runs", SRC, b"x", x64()) {
+        assert_eq!(result, (b"ABx".to_vec(), 0));
+    }
+}
+
+#[test]
+fn x64_elf_leaves_the_tape() {
+    let options = Options {
+        tape_size: 4,
+        ..x64()
+    };
+    let cases: [(&str, &[u8]); 2] = [("+.<", &[1]), ("+.[>+]", &[1])];
+    for (index, (src, printed)) in cases.iter().enumerate() {
+        let name = format!("x64_elf_leaves_the_tape-{}", index);
+        if let Some(result) = run_elf(&name, src, b"", options.clone()) {
+            assert_eq!(result, (printed.to_vec(), EXIT_TAPE), "{:?}", src);
+        }
+    }
+}
+
+#[test]
+fn x64_elf_cell_overflow() {
+    let options = Options {
+        checked_cells: true,
+        ..x64()
+    };
+    if let Some(result) = run_elf("x64_elf_cell_overflow", "+.--", b"", options) {
+        assert_eq!(result, (vec![1], EXIT_CELL));
+    }
+}
@@ -0,0 +1,96 @@
+//! Runtime errors point at the same instruction whatever the backend.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use bfjit::bfparser::frontend::parser::Span;
+use bfjit::bftype::bfcate::bfcate::VMBackendType;
+use bfjit::{Options, Program};
+
+/// `(line, col, message)` of the error of `src` at each `-O` level, on
+/// which every backend must agree.
+fn error_at(src: &str, options: Options) -> Vec<(u32, u32, String)> {
+    let mut result = vec![];
+    for opt_level in 0..=3 {
+        let mut errors: Vec<(VMBackendType, Option<Span>, String)> = vec![];
+        for backend in common::backends() {
+            let options = Options {
+                backend: backend.clone(),
+                opt_level,
+                ..options.clone()
+            };
+            let program = Program::compile(src, options).unwrap();
+            let error = program.run(&b""[..], vec![]).unwrap_err();
+            errors.push((backend, error.span, error.kind.to_string()));
+        }
+        let (_, first, message) = errors[0].clone();
+        let first = first.expect("span");
+        for (backend, span, text) in &errors {
+            assert_eq!(
+                (*span, text),
+                (Some(first), &message),
+                "{:?} -O{} on {:?}",
+                backend,
+                opt_level,
+                src
+            );
+        }
+        result.push((first.line, first.col, message));
+    }
+    return result;
+}
+
+/// The error expected at every `-O` level.
+fn everywhere(line: u32, col: u32, message: &str) -> Vec<(u32, u32, String)> {
+    return vec![(line, col, String::from(message)); 4];
+}
+
+#[test]
+fn left_of_the_tape() {
+    assert_eq!(
+        error_at("+.\n<", Options::default()),
+        everywhere(2, 1, "Pointer moved below cell 0")
+    );
+}
+
+#[test]
+fn right_of_the_tape() {
+    let options = Options {
+        tape_size: 4,
+        ..Default::default()
+    };
+    assert_eq!(
+        error_at("+[>+]", options),
+        everywhere(1, 3, "Pointer moved past the last cell")
+    );
+}
+
+#[test]
+fn offset_off_the_tape() {
+    // at -O3 the moves are folded into the offset of `.`
+    assert_eq!(
+        error_at("+\n <<.", Options::default()),
+        everywhere(2, 2, "Pointer moved below cell 0")
+    );
+}
+
+#[test]
+fn end_of_input() {
+    assert_eq!(
+        error_at("++.,", Options::default()),
+        everywhere(1, 4, "IO Error")
+    );
+}
+
+#[test]
+fn cell_overflow() {
+    let options = Options {
+        checked_cells: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        error_at(".\n>-", options),
+        everywhere(2, 2, "Cell overflow")
+    );
+}
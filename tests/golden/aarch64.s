@@ -0,0 +1,130 @@
+004000b0  00 00 80 d2                    mov x0, #0
+004000b4  01 00 80 d2                    mov x1, #0
+004000b8  21 00 c0 f2                    movk x1, #1, lsl #32
+004000bc  02 a6 8e d2                    mov x2, #0x7530
+004000c0  22 00 c0 f2                    movk x2, #1, lsl #32
+004000c4  03 00 00 94                    bl #0x4000d0
+004000c8  a8 0b 80 d2                    mov x8, #0x5d
+004000cc  01 00 00 d4                    svc #0
+004000d0  fd 7b bd a9                    stp x29, x30, [sp, #-0x30]!
+004000d4  fd 03 00 91                    mov x29, sp
+004000d8  f3 53 01 a9                    stp x19, x20, [sp, #0x10]
+004000dc  f5 5b 02 a9                    stp x21, x22, [sp, #0x20]
+004000e0  f3 03 00 aa                    mov x19, x0
+004000e4  f4 03 01 aa                    mov x20, x1
+004000e8  f5 03 02 aa                    mov x21, x2
+004000ec  f6 03 01 aa                    mov x22, x1
+; 0: add 65 @1
+004000f0  2a 00 80 d2                    mov x10, #1
+004000f4  a9 02 16 cb                    sub x9, x21, x22
+004000f8  3f 01 0a eb                    cmp x9, x10
+004000fc  48 00 00 54                    b.hi #0x400104
+00400100  34 00 00 94                    bl #0x4001d0
+00400104  c9 06 00 91                    add x9, x22, #1
+00400108  2b 01 40 39                    ldrb w11, [x9]
+0040010c  2c 08 80 d2                    mov x12, #0x41
+00400110  6b 01 0c 8b                    add x11, x11, x12
+00400114  2b 01 00 39                    strb w11, [x9]
+; 1: output @1
+00400118  c1 06 00 91                    add x1, x22, #1
+0040011c  e0 03 13 aa                    mov x0, x19
+00400120  47 00 00 94                    bl #0x40023c
+00400124  e0 05 00 b5                    cbnz x0, #0x4001e0
+; 2: add 1 @1
+00400128  2a 00 80 d2                    mov x10, #1
+0040012c  a9 02 16 cb                    sub x9, x21, x22
+00400130  3f 01 0a eb                    cmp x9, x10
+00400134  48 00 00 54                    b.hi #0x40013c
+00400138  26 00 00 94                    bl #0x4001d0
+0040013c  c9 06 00 91                    add x9, x22, #1
+00400140  2b 01 40 39                    ldrb w11, [x9]
+00400144  2c 00 80 d2                    mov x12, #1
+00400148  6b 01 0c 8b                    add x11, x11, x12
+0040014c  2b 01 00 39                    strb w11, [x9]
+; 3: output @1
+00400150  c1 06 00 91                    add x1, x22, #1
+00400154  e0 03 13 aa                    mov x0, x19
+00400158  39 00 00 94                    bl #0x40023c
+0040015c  20 04 00 b5                    cbnz x0, #0x4001e0
+; 4: input @1
+00400160  2a 00 80 d2                    mov x10, #1
+00400164  a9 02 16 cb                    sub x9, x21, x22
+00400168  3f 01 0a eb                    cmp x9, x10
+0040016c  48 00 00 54                    b.hi #0x400174
+00400170  18 00 00 94                    bl #0x4001d0
+00400174  c1 06 00 91                    add x1, x22, #1
+00400178  e0 03 13 aa                    mov x0, x19
+0040017c  1d 00 00 94                    bl #0x4001f0
+00400180  00 03 00 b5                    cbnz x0, #0x4001e0
+; 5: output @1
+00400184  2a 00 80 d2                    mov x10, #1
+00400188  a9 02 16 cb                    sub x9, x21, x22
+0040018c  3f 01 0a eb                    cmp x9, x10
+00400190  48 00 00 54                    b.hi #0x400198
+00400194  0f 00 00 94                    bl #0x4001d0
+00400198  c1 06 00 91                    add x1, x22, #1
+0040019c  e0 03 13 aa                    mov x0, x19
+004001a0  27 00 00 94                    bl #0x40023c
+004001a4  e0 01 00 b5                    cbnz x0, #0x4001e0
+; 6: right 1
+004001a8  2a 00 80 d2                    mov x10, #1
+004001ac  a9 02 16 cb                    sub x9, x21, x22
+004001b0  3f 01 0a eb                    cmp x9, x10
+004001b4  48 00 00 54                    b.hi #0x4001bc
+004001b8  06 00 00 94                    bl #0x4001d0
+004001bc  d6 06 00 91                    add x22, x22, #1
+004001c0  00 00 80 d2                    mov x0, #0
+004001c4  07 00 00 14                    b #0x4001e0
+004001c8  21 00 80 d2                    mov x1, #1
+004001cc  02 00 00 14                    b #0x4001d4
+004001d0  01 00 80 d2                    mov x1, #0
+004001d4  e0 03 1e aa                    mov x0, x30
+004001d8  20 00 00 94                    bl #0x400258
+004001dc  01 00 00 14                    b #0x4001e0
+004001e0  f5 5b 42 a9                    ldp x21, x22, [sp, #0x20]
+004001e4  f3 53 41 a9                    ldp x19, x20, [sp, #0x10]
+004001e8  fd 7b c3 a8                    ldp x29, x30, [sp], #0x30
+004001ec  c0 03 5f d6                    ret
+004001f0  e1 7f bf a9                    stp x1, xzr, [sp, #-0x10]!
+004001f4  00 00 80 d2                    mov x0, #0
+004001f8  e1 23 00 91                    add x1, sp, #8
+004001fc  22 00 80 d2                    mov x2, #1
+00400200  e8 07 80 d2                    mov x8, #0x3f
+00400204  01 00 00 d4                    svc #0
+00400208  e9 03 40 f9                    ldr x9, [sp]
+0040020c  1f 04 00 f1                    cmp x0, #1
+00400210  81 00 00 54                    b.ne #0x400220
+00400214  eb 23 40 39                    ldrb w11, [sp, #8]
+00400218  2b 01 00 39                    strb w11, [x9]
+0040021c  03 00 00 14                    b #0x400228
+00400220  80 00 00 b5                    cbnz x0, #0x400230
+00400224  03 00 00 14                    b #0x400230
+00400228  00 00 80 d2                    mov x0, #0
+0040022c  02 00 00 14                    b #0x400234
+00400230  20 00 80 d2                    mov x0, #1
+00400234  ff 43 00 91                    add sp, sp, #0x10
+00400238  c0 03 5f d6                    ret
+0040023c  20 00 80 d2                    mov x0, #1
+00400240  22 00 80 d2                    mov x2, #1
+00400244  08 08 80 d2                    mov x8, #0x40
+00400248  01 00 00 d4                    svc #0
+0040024c  1f 04 00 f1                    cmp x0, #1
+00400250  e0 07 9f 9a                    cset x0, ne
+00400254  c0 03 5f d6                    ret
+00400258  40 00 80 d2                    mov x0, #2
+0040025c  c0 03 5f d6                    ret
+00400260  60 00 80 d2                    mov x0, #3
+00400264  c0 03 5f d6                    ret
+00400268  cb 02 40 39                    ldrb w11, [x22]
+0040026c  0b 01 00 b4                    cbz x11, #0x40028c
+00400270  c9 02 02 8b                    add x9, x22, x2
+00400274  3f 01 14 eb                    cmp x9, x20
+00400278  e3 00 00 54                    b.lo #0x400294
+0040027c  3f 01 15 eb                    cmp x9, x21
+00400280  a2 00 00 54                    b.hs #0x400294
+00400284  f6 03 09 aa                    mov x22, x9
+00400288  f8 ff ff 17                    b #0x400268
+0040028c  00 00 80 d2                    mov x0, #0
+00400290  c0 03 5f d6                    ret
+00400294  40 00 80 d2                    mov x0, #2
+00400298  c0 03 5f d6                    ret
@@ -0,0 +1,134 @@
+08048074  83 ec 04                       sub esp, 4
+08048077  68 30 75 00 10                 push 0x10007530
+0804807c  68 00 00 00 10                 push 0x10000000
+08048081  6a 00                          push 0
+08048083  e8 09 00 00 00                 call 0x8048091
+08048088  89 c3                          mov ebx, eax
+0804808a  b8 01 00 00 00                 mov eax, 1
+0804808f  cd 80                          int 0x80
+08048091  53                             push ebx
+08048092  56                             push esi
+08048093  57                             push edi
+08048094  55                             push ebp
+08048095  83 ec 04                       sub esp, 4
+08048098  8b 5c 24 18                    mov ebx, dword ptr [esp + 0x18]
+0804809c  8b 74 24 1c                    mov esi, dword ptr [esp + 0x1c]
+080480a0  8b 7c 24 20                    mov edi, dword ptr [esp + 0x20]
+080480a4  89 f1                          mov ecx, esi
+; 0: add 65 @1
+080480a6  89 f8                          mov eax, edi
+080480a8  29 c8                          sub eax, ecx
+080480aa  3d 01 00 00 00                 cmp eax, 1
+080480af  0f 86 d1 00 00 00              jbe 0x8048186
+080480b5  80 81 01 00 00 00 41           add byte ptr [ecx + 1], 0x41
+; 1: output @1
+080480bc  89 cd                          mov ebp, ecx
+080480be  8d 81 01 00 00 00              lea eax, [ecx + 1]
+080480c4  50                             push eax
+080480c5  53                             push ebx
+080480c6  e8 21 01 00 00                 call 0x80481ec
+080480cb  83 c4 08                       add esp, 8
+080480ce  85 c0                          test eax, eax
+080480d0  0f 85 c3 00 00 00              jne 0x8048199
+080480d6  89 e9                          mov ecx, ebp
+; 2: add 1 @1
+080480d8  89 f8                          mov eax, edi
+080480da  29 c8                          sub eax, ecx
+080480dc  3d 01 00 00 00                 cmp eax, 1
+080480e1  0f 86 9f 00 00 00              jbe 0x8048186
+080480e7  80 81 01 00 00 00 01           add byte ptr [ecx + 1], 1
+; 3: output @1
+080480ee  89 cd                          mov ebp, ecx
+080480f0  8d 81 01 00 00 00              lea eax, [ecx + 1]
+080480f6  50                             push eax
+080480f7  53                             push ebx
+080480f8  e8 ef 00 00 00                 call 0x80481ec
+080480fd  83 c4 08                       add esp, 8
+08048100  85 c0                          test eax, eax
+08048102  0f 85 91 00 00 00              jne 0x8048199
+08048108  89 e9                          mov ecx, ebp
+; 4: input @1
+0804810a  89 f8                          mov eax, edi
+0804810c  29 c8                          sub eax, ecx
+0804810e  3d 01 00 00 00                 cmp eax, 1
+08048113  0f 86 6d 00 00 00              jbe 0x8048186
+08048119  89 cd                          mov ebp, ecx
+0804811b  8d 81 01 00 00 00              lea eax, [ecx + 1]
+08048121  50                             push eax
+08048122  53                             push ebx
+08048123  e8 79 00 00 00                 call 0x80481a1
+08048128  83 c4 08                       add esp, 8
+0804812b  85 c0                          test eax, eax
+0804812d  0f 85 66 00 00 00              jne 0x8048199
+08048133  89 e9                          mov ecx, ebp
+; 5: output @1
+08048135  89 f8                          mov eax, edi
+08048137  29 c8                          sub eax, ecx
+08048139  3d 01 00 00 00                 cmp eax, 1
+0804813e  0f 86 42 00 00 00              jbe 0x8048186
+08048144  89 cd                          mov ebp, ecx
+08048146  8d 81 01 00 00 00              lea eax, [ecx + 1]
+0804814c  50                             push eax
+0804814d  53                             push ebx
+0804814e  e8 99 00 00 00                 call 0x80481ec
+08048153  83 c4 08                       add esp, 8
+08048156  85 c0                          test eax, eax
+08048158  0f 85 3b 00 00 00              jne 0x8048199
+0804815e  89 e9                          mov ecx, ebp
+; 6: right 1
+08048160  89 f8                          mov eax, edi
+08048162  29 c8                          sub eax, ecx
+08048164  3d 01 00 00 00                 cmp eax, 1
+08048169  0f 86 17 00 00 00              jbe 0x8048186
+0804816f  81 c1 01 00 00 00              add ecx, 1
+08048175  31 c0                          xor eax, eax
+08048177  e9 1d 00 00 00                 jmp 0x8048199
+0804817c  b8 01 00 00 00                 mov eax, 1
+08048181  e9 02 00 00 00                 jmp 0x8048188
+08048186  31 c0                          xor eax, eax
+08048188  83 ec 04                       sub esp, 4
+0804818b  50                             push eax
+0804818c  e8 7c 00 00 00                 call 0x804820d
+08048191  83 c4 08                       add esp, 8
+08048194  e9 00 00 00 00                 jmp 0x8048199
+08048199  83 c4 04                       add esp, 4
+0804819c  5d                             pop ebp
+0804819d  5f                             pop edi
+0804819e  5e                             pop esi
+0804819f  5b                             pop ebx
+080481a0  c3                             ret
+080481a1  53                             push ebx
+080481a2  6a 00                          push 0
+080481a4  b8 03 00 00 00                 mov eax, 3
+080481a9  31 db                          xor ebx, ebx
+080481ab  89 e1                          mov ecx, esp
+080481ad  ba 01 00 00 00                 mov edx, 1
+080481b2  cd 80                          int 0x80
+080481b4  8b 54 24 10                    mov edx, dword ptr [esp + 0x10]
+080481b8  3d 01 00 00 00                 cmp eax, 1
+080481bd  0f 85 0b 00 00 00              jne 0x80481ce
+080481c3  0f b6 04 24                    movzx eax, byte ptr [esp]
+080481c7  88 02                          mov byte ptr [edx], al
+080481c9  e9 0d 00 00 00                 jmp 0x80481db
+080481ce  85 c0                          test eax, eax
+080481d0  0f 85 0c 00 00 00              jne 0x80481e2
+080481d6  e9 07 00 00 00                 jmp 0x80481e2
+080481db  31 c0                          xor eax, eax
+080481dd  e9 05 00 00 00                 jmp 0x80481e7
+080481e2  b8 01 00 00 00                 mov eax, 1
+080481e7  83 c4 04                       add esp, 4
+080481ea  5b                             pop ebx
+080481eb  c3                             ret
+080481ec  53                             push ebx
+080481ed  b8 04 00 00 00                 mov eax, 4
+080481f2  bb 01 00 00 00                 mov ebx, 1
+080481f7  8b 4c 24 0c                    mov ecx, dword ptr [esp + 0xc]
+080481fb  ba 01 00 00 00                 mov edx, 1
+08048200  cd 80                          int 0x80
+08048202  5b                             pop ebx
+08048203  83 e8 01                       sub eax, 1
+08048206  0f 95 c0                       setne al
+08048209  0f b6 c0                       movzx eax, al
+0804820c  c3                             ret
+0804820d  b8 02 00 00 00                 mov eax, 2
+08048212  c3                             ret
@@ -0,0 +1,80 @@
+//! `Program::run` reads only what the program consumes unless asked to read
+//! ahead, and retries reads interrupted by a signal.
+
+#![allow(clippy::needless_return)]
+
+mod common;
+
+use std::io::{Cursor, ErrorKind, Read};
+
+use bfjit::bftype::bfcate::bfcate::{VMBackendType, VMEofType};
+use bfjit::bfvm::bfjit::vm;
+use bfjit::{Options, Program};
+
+/// Fails every other read with `Interrupted`.
+struct Interrupting<R> {
+    inner: R,
+    interrupt: bool,
+}
+
+impl<R: Read> Read for Interrupting<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(ErrorKind::Interrupted.into());
+        }
+        return self.inner.read(buf);
+    }
+}
+
+#[test]
+fn leaves_unread_input() {
+    for backend in common::backends() {
+        let options = Options {
+            backend: backend.clone(),
+            ..Default::default()
+        };
+        let program = Program::compile(",>,", options).unwrap();
+        let mut input = Cursor::new(b"abcdef".to_vec());
+        program.run(&mut input, vec![]).unwrap();
+        assert_eq!(input.position(), 2, "{:?}", backend);
+    }
+}
+
+#[test]
+fn reads_ahead_when_asked() {
+    if !cfg!(target_arch = "x86_64") {
+        return;
+    }
+    let options = Options {
+        backend: VMBackendType::JIT,
+        input_buffer: vm::INPUT_BUFFER_SIZE,
+        ..Default::default()
+    };
+    let program = Program::compile(",>,", options).unwrap();
+    let mut input = Cursor::new(b"abcdef".to_vec());
+    program.run(&mut input, vec![]).unwrap();
+    assert_eq!(input.position(), 6);
+}
+
+#[test]
+fn retries_interrupted_reads() {
+    for backend in common::backends() {
+        for input_buffer in [0, vm::INPUT_BUFFER_SIZE] {
+            let options = Options {
+                backend: backend.clone(),
+                eof_type: VMEofType::Zero,
+                input_buffer,
+                ..Default::default()
+            };
+            let program = Program::compile(",[.,]", options).unwrap();
+            let input = Interrupting {
+                inner: &b"hello"[..],
+                interrupt: false,
+            };
+            let mut output = vec![];
+            program.run(input, &mut output).unwrap();
+            assert_eq!(output, b"hello", "{:?}", backend);
+        }
+    }
+}